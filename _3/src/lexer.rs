@@ -0,0 +1,221 @@
+use std::ops::Range;
+
+// longest identifier tail kept around, instruction names must fit in it
+pub const MAX_WORD_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    // run of [A-Za-z_'], only the last MAX_WORD_LEN bytes are kept
+    Word(String),
+    // run of ascii digits, value is None when it does not fit a u32
+    Number { value: Option<u32>, digits: usize },
+    LParen,
+    RParen,
+    Comma,
+    Other(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme {
+    pub token: Token,
+    pub span: Range<usize>,
+    // 1-based line the lexeme starts on
+    pub line: usize,
+}
+
+#[derive(Debug)]
+enum Pending {
+    Word(String),
+    Number { value: Option<u32>, digits: usize },
+}
+
+// Push based lexer: bytes can be fed in arbitrary slices and tokens that
+// straddle two slices are only emitted once they are complete.
+#[derive(Debug)]
+pub struct Lexer {
+    offset: usize,
+    line: usize,
+    start: usize,
+    start_line: usize,
+    pending: Option<Pending>,
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            start: 0,
+            start_line: 1,
+            pending: None,
+        }
+    }
+}
+
+pub fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b == b'\''
+}
+
+impl Lexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn feed(&mut self, bytes: &[u8], mut emit: impl FnMut(Lexeme)) {
+        for &b in bytes {
+            self.push(b, &mut emit);
+        }
+    }
+
+    pub fn finish(&mut self, mut emit: impl FnMut(Lexeme)) {
+        self.flush(&mut emit);
+    }
+
+    fn push(&mut self, b: u8, emit: &mut impl FnMut(Lexeme)) {
+        match (&mut self.pending, b) {
+            (Some(Pending::Word(w)), b) if is_word_byte(b) => {
+                if w.len() == MAX_WORD_LEN {
+                    w.remove(0);
+                }
+                w.push(b as char);
+            }
+            (Some(Pending::Number { value, digits }), b) if b.is_ascii_digit() => {
+                *value = value
+                    .and_then(|v| v.checked_mul(10))
+                    .and_then(|v| v.checked_add((b - b'0') as u32));
+                *digits += 1;
+            }
+            _ => {
+                self.flush(emit);
+                self.start = self.offset;
+                self.start_line = self.line;
+                match b {
+                    b'(' => self.emit_single(Token::LParen, emit),
+                    b')' => self.emit_single(Token::RParen, emit),
+                    b',' => self.emit_single(Token::Comma, emit),
+                    b if b.is_ascii_digit() => {
+                        self.pending = Some(Pending::Number {
+                            value: Some((b - b'0') as u32),
+                            digits: 1,
+                        })
+                    }
+                    b if is_word_byte(b) => self.pending = Some(Pending::Word((b as char).into())),
+                    b => self.emit_single(Token::Other(b), emit),
+                }
+            }
+        }
+        self.offset += 1;
+        if b == b'\n' {
+            self.line += 1;
+        }
+    }
+
+    fn emit_single(&mut self, token: Token, emit: &mut impl FnMut(Lexeme)) {
+        emit(Lexeme {
+            token,
+            span: self.offset..self.offset + 1,
+            line: self.line,
+        });
+    }
+
+    fn flush(&mut self, emit: &mut impl FnMut(Lexeme)) {
+        if let Some(pending) = self.pending.take() {
+            let token = match pending {
+                Pending::Word(w) => Token::Word(w),
+                Pending::Number { value, digits } => Token::Number { value, digits },
+            };
+            emit(Lexeme {
+                token,
+                span: self.start..self.offset,
+                line: self.start_line,
+            });
+        }
+    }
+}
+
+pub fn tokenize(input: &str) -> Vec<Lexeme> {
+    let mut lexer = Lexer::new();
+    let mut result = Vec::new();
+    lexer.feed(input.as_bytes(), |it| result.push(it));
+    lexer.finish(|it| result.push(it));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<Token> {
+        tokenize(input).into_iter().map(|it| it.token).collect()
+    }
+
+    #[test]
+    fn should_tokenize_call() {
+        let expected = vec![
+            Token::Word("xmul".into()),
+            Token::LParen,
+            Token::Number {
+                value: Some(2),
+                digits: 1,
+            },
+            Token::Comma,
+            Token::Number {
+                value: Some(40),
+                digits: 3,
+            },
+            Token::RParen,
+            Token::Other(b'%'),
+        ];
+        let actual = tokens("xmul(2,040)%");
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_keep_apostrophe_in_words() {
+        let expected = vec![Token::Word("don't".into()), Token::LParen, Token::RParen];
+        let actual = tokens("don't()");
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_track_spans() {
+        let expected = vec![0..3, 3..4, 4..6, 6..7];
+        let actual: Vec<_> = tokenize("mul(12)").into_iter().map(|it| it.span).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_track_lines() {
+        let expected = vec![1, 1, 2, 2, 3];
+        let actual: Vec<_> = tokenize("ab\ncd\n12")
+            .into_iter()
+            .map(|it| it.line)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_join_tokens_across_chunks() {
+        let mut lexer = Lexer::new();
+        let mut actual = Vec::new();
+        for chunk in ["m", "ul(1", "23,", "4)"] {
+            lexer.feed(chunk.as_bytes(), |it| actual.push(it));
+        }
+        lexer.finish(|it| actual.push(it));
+        assert_eq!(actual, tokenize("mul(123,4)"));
+    }
+
+    #[test]
+    fn should_not_overflow_long_numbers() {
+        let expected = vec![Token::Number {
+            value: None,
+            digits: 12,
+        }];
+        let actual = tokens("123456789012");
+        assert_eq!(expected, actual);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod lexer;
+pub mod program;
+pub mod stream;
+pub mod trace;
@@ -1,15 +1,30 @@
-use std::{error::Error, fs::File, io::Read};
-
-use regex::Regex;
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{self, Read},
+};
+
+use _3::{
+    program::{Definition, Instruction, InstructionSet, Machine},
+    stream::{decode_reader, CHUNK_SIZE},
+    trace::{highlight, trace},
+};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut f = File::open("puzzle.txt")?;
-
-    let mut buf = String::new();
-    f.read_to_string(&mut buf);
+    // --trace lists every instruction, --highlight colours them in the input,
+    // both need the whole input in memory
+    for arg in env::args().skip(1) {
+        let buf = fs::read_to_string("puzzle.txt")?;
+        let steps = trace(&buf, &InstructionSet::puzzle());
+        match arg.as_str() {
+            "--trace" => steps.iter().for_each(|step| println!("{}", step)),
+            "--highlight" => println!("{}", highlight(&buf, &steps)),
+            _ => return Err(format!("unknown argument {}", arg).into()),
+        }
+    }
 
-    let sum_of_multiples = sum_mult(matched_mul(&buf));
-    let sum_of_enabled_multiples = sum_mult_parsed_enabled(&buf);
+    let (sum_of_multiples, sum_of_enabled_multiples) = sum_both(File::open("puzzle.txt")?)?;
 
     println!("result 1: {}", sum_of_multiples);
     println!("result 2: {}", sum_of_enabled_multiples);
@@ -17,63 +32,51 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn matched_enabled_mul(s: &str) -> String {
-    let re = Regex::new(r"do\(\)|don't\(\)|mul\(\d+,\d+\)").unwrap();
-    let mut enabled = true;
-    let mut cleaned_muls = String::new();
-
-    for cap in re.find_iter(s) {
-        let token = cap.as_str();
-
-        match token {
-            "do()" => enabled = true,
-            "don't()" => enabled = false,
-            _ => {
-                if enabled {
-                    cleaned_muls.push_str(token);
-                }
-            }
+// Both parts from a single pass over the input: everything is decoded with
+// the puzzle's set, the machine for part 1 only knows `mul` and only gets
+// those.
+fn sum_both(r: impl Read) -> io::Result<(u64, u64)> {
+    let mul_only = InstructionSet::empty().register(Definition::mul());
+    let puzzle = InstructionSet::puzzle();
+    let mut all = Machine::new(&mul_only);
+    let mut enabled = Machine::new(&puzzle);
+    decode_reader(r, &puzzle, CHUNK_SIZE, |decoded| {
+        if let Instruction::Mul(..) = decoded.instruction {
+            all.step(&decoded.instruction);
         }
-    }
-    cleaned_muls
-}
-
-fn sum_mult_parsed_enabled(s: &str) -> u32 {
-    sum_mult(matched_mul(&matched_enabled_mul(&s)))
-}
-
-fn matched_mul(s: &str) -> Vec<(u32, u32)> {
-    let re = Regex::new(r"mul\((\d+),(\d+)\)").unwrap();
-    re.captures_iter(s)
-        .map(|cap| {
-            let a = cap[1].parse::<u32>().unwrap();
-            let b = cap[2].parse::<u32>().unwrap();
-            (a, b)
-        })
-        .collect()
-}
-
-fn sum_mult(v: Vec<(u32, u32)>) -> u32 {
-    v.iter().map(|(a, b)| a * b).sum()
+        enabled.step(&decoded.instruction);
+    })?;
+    Ok((all.state.total, enabled.state.total))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use _3::stream::run_reader;
+
+    fn sum_mult(r: impl Read) -> io::Result<u64> {
+        Ok(sum_both(r)?.0)
+    }
+
+    fn sum_mult_parsed_enabled(r: impl Read) -> io::Result<u64> {
+        Ok(sum_both(r)?.1)
+    }
 
     #[test]
     fn should_match_only_valid() {
         let given = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
-        let expected = vec![(2, 4), (5, 5), (11, 8), (8, 5)];
-        let actual = matched_mul(&given);
+        let expected = 2 * 4 + 5 * 5 + 11 * 8 + 8 * 5;
+        let actual = sum_mult(given.as_bytes()).unwrap();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn should_match_context() {
         let given = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
-        let expected = "mul(2,4)mul(8,5)".to_string();
-        let actual = matched_enabled_mul(&given);
+        let expected = 2 * 4 + 8 * 5;
+        let actual = run_reader(given.as_bytes(), &InstructionSet::puzzle(), 1)
+            .unwrap()
+            .total;
         assert_eq!(actual, expected);
     }
 
@@ -81,7 +84,7 @@ mod tests {
     fn should_calculate_only_enabled() {
         let given = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
         let expected = 48;
-        let actual = sum_mult_parsed_enabled(&given);
+        let actual = sum_mult_parsed_enabled(given.as_bytes()).unwrap();
         assert_eq!(actual, expected);
     }
 }
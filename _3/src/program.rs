@@ -0,0 +1,503 @@
+use std::{collections::HashMap, ops::Range, ops::RangeInclusive};
+
+use crate::lexer::{is_word_byte, Lexeme, Lexer, Token, MAX_WORD_LEN};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(u32, u32),
+    Do,
+    Dont,
+    Custom { name: String, args: Vec<u32> },
+}
+
+// Semantics of a registered instruction, it only runs while enabled when the
+// definition is conditional.
+pub type Exec = fn(&mut State, &[u32]);
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Mul,
+    Do,
+    Dont,
+    Custom { exec: Exec, conditional: bool },
+}
+
+// Argument grammar: a plain decimal with a bounded number of digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operand {
+    digits: RangeInclusive<usize>,
+}
+
+impl Operand {
+    pub fn digits(digits: RangeInclusive<usize>) -> Self {
+        Self { digits }
+    }
+
+    fn accepts(&self, token: &Token) -> Option<u32> {
+        match token {
+            Token::Number {
+                value: Some(v),
+                digits,
+            } if self.digits.contains(digits) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Definition {
+    name: String,
+    operands: Vec<Operand>,
+    kind: Kind,
+}
+
+impl Definition {
+    pub fn mul() -> Self {
+        Self {
+            name: "mul".into(),
+            operands: vec![Operand::digits(1..=3), Operand::digits(1..=3)],
+            kind: Kind::Mul,
+        }
+    }
+
+    pub fn enable() -> Self {
+        Self {
+            name: "do".into(),
+            operands: vec![],
+            kind: Kind::Do,
+        }
+    }
+
+    pub fn disable() -> Self {
+        Self {
+            name: "don't".into(),
+            operands: vec![],
+            kind: Kind::Dont,
+        }
+    }
+
+    // `name` may only contain ascii letters, '_' and '\'' and be at most
+    // MAX_WORD_LEN long, anything else could never be lexed as one word.
+    pub fn custom(name: &str, operands: Vec<Operand>, exec: Exec, conditional: bool) -> Self {
+        assert!(
+            !name.is_empty() && name.len() <= MAX_WORD_LEN && name.bytes().all(is_word_byte),
+            "instruction name {:?} can never be lexed",
+            name
+        );
+        Self {
+            name: name.into(),
+            operands,
+            kind: Kind::Custom { exec, conditional },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decode(&self, args: &[u32]) -> Instruction {
+        match self.kind {
+            Kind::Mul => Instruction::Mul(args[0], args[1]),
+            Kind::Do => Instruction::Do,
+            Kind::Dont => Instruction::Dont,
+            Kind::Custom { .. } => Instruction::Custom {
+                name: self.name.clone(),
+                args: args.to_vec(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    defs: Vec<Definition>,
+    by_name: HashMap<String, usize>,
+}
+
+impl InstructionSet {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    // mul, do and don't as described by the puzzle
+    pub fn puzzle() -> Self {
+        Self::empty()
+            .register(Definition::mul())
+            .register(Definition::enable())
+            .register(Definition::disable())
+    }
+
+    // a later definition with the same name replaces the earlier one
+    pub fn register(mut self, def: Definition) -> Self {
+        match self.by_name.get(&def.name) {
+            Some(&i) => self.defs[i] = def,
+            None => {
+                self.by_name.insert(def.name.clone(), self.defs.len());
+                self.defs.push(def);
+            }
+        }
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&Definition> {
+        self.by_name.get(name).map(|&i| &self.defs[i])
+    }
+
+    // definitions whose name ends `word`, longest name first
+    fn candidates(&self, word: &str) -> Vec<usize> {
+        let mut result: Vec<usize> = (0..self.defs.len())
+            .filter(|&i| word.ends_with(&self.defs[i].name))
+            .collect();
+        result.sort_by_key(|&i| std::cmp::Reverse(self.defs[i].name.len()));
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub instruction: Instruction,
+    pub span: Range<usize>,
+    pub line: usize,
+    // source text, operands keep their leading zeros
+    pub text: String,
+}
+
+#[derive(Debug)]
+enum ParseState {
+    Idle {
+        word: Option<(String, usize, usize)>,
+    },
+    Call {
+        candidates: Vec<usize>,
+        name_end: usize,
+        line: usize,
+        args: Vec<u32>,
+        text: String,
+        expect_comma: bool,
+    },
+}
+
+// Turns lexemes into instructions. A call is a registered name directly
+// followed by `(`, its comma separated operands and `)`, nothing in between.
+#[derive(Debug)]
+pub struct Parser<'a> {
+    set: &'a InstructionSet,
+    state: ParseState,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(set: &'a InstructionSet) -> Self {
+        Self {
+            set,
+            state: ParseState::Idle { word: None },
+        }
+    }
+
+    pub fn push(&mut self, lexeme: Lexeme) -> Option<Decoded> {
+        if let ParseState::Call {
+            candidates,
+            name_end,
+            line,
+            args,
+            text,
+            expect_comma,
+        } = &mut self.state
+        {
+            let defs = &self.set.defs;
+            let n = args.len();
+            match &lexeme.token {
+                Token::RParen => {
+                    let done = candidates
+                        .iter()
+                        .map(|&i| &defs[i])
+                        .find(|def| def.operands.len() == n && (n == 0 || *expect_comma));
+                    if let Some(def) = done {
+                        let decoded = Decoded {
+                            instruction: def.decode(args),
+                            span: *name_end - def.name.len()..lexeme.span.end,
+                            line: *line,
+                            text: format!("{}{})", def.name, text),
+                        };
+                        self.state = ParseState::Idle { word: None };
+                        return Some(decoded);
+                    }
+                }
+                Token::Comma if *expect_comma => {
+                    candidates.retain(|&i| defs[i].operands.len() > n);
+                    text.push(',');
+                    *expect_comma = false;
+                }
+                token if !*expect_comma => {
+                    candidates.retain(|&i| {
+                        defs[i]
+                            .operands
+                            .get(n)
+                            .and_then(|op| op.accepts(token))
+                            .is_some()
+                    });
+                    if let Some(v) = candidates
+                        .first()
+                        .and_then(|&i| defs[i].operands[n].accepts(token))
+                    {
+                        if let Token::Number { digits, .. } = token {
+                            text.push_str(&format!("{:0width$}", v, width = digits));
+                        }
+                        args.push(v);
+                        *expect_comma = true;
+                    }
+                }
+                _ => candidates.clear(),
+            }
+            if !candidates.is_empty() && !matches!(lexeme.token, Token::RParen) {
+                return None;
+            }
+            // the rejected lexeme may start the next instruction
+            self.state = ParseState::Idle { word: None };
+        }
+
+        match lexeme.token {
+            Token::Word(w) => {
+                self.state = ParseState::Idle {
+                    word: Some((w, lexeme.span.end, lexeme.line)),
+                };
+            }
+            Token::LParen => {
+                if let ParseState::Idle {
+                    word: Some((w, end, line)),
+                } = &self.state
+                {
+                    let candidates = self.set.candidates(w);
+                    if !candidates.is_empty() {
+                        self.state = ParseState::Call {
+                            candidates,
+                            name_end: *end,
+                            line: *line,
+                            args: Vec::new(),
+                            text: "(".into(),
+                            expect_comma: false,
+                        };
+                        return None;
+                    }
+                }
+                self.state = ParseState::Idle { word: None };
+            }
+            _ => self.state = ParseState::Idle { word: None },
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    pub enabled: bool,
+    pub total: u64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Executed,
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct Machine<'a> {
+    set: &'a InstructionSet,
+    pub state: State,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(set: &'a InstructionSet) -> Self {
+        Self {
+            set,
+            state: State::default(),
+        }
+    }
+
+    pub fn step(&mut self, instruction: &Instruction) -> Outcome {
+        match instruction {
+            Instruction::Do => self.state.enabled = true,
+            Instruction::Dont => self.state.enabled = false,
+            Instruction::Mul(a, b) => {
+                if !self.state.enabled {
+                    return Outcome::Skipped;
+                }
+                self.state.total += *a as u64 * *b as u64;
+            }
+            Instruction::Custom { name, args } => {
+                let Some(Kind::Custom { exec, conditional }) = self.set.get(name).map(|d| d.kind)
+                else {
+                    panic!("instruction {} is not registered", name);
+                };
+                if conditional && !self.state.enabled {
+                    return Outcome::Skipped;
+                }
+                exec(&mut self.state, args);
+            }
+        }
+        Outcome::Executed
+    }
+}
+
+pub fn decode(input: &str, set: &InstructionSet) -> Vec<Decoded> {
+    let mut lexer = Lexer::new();
+    let mut parser = Parser::new(set);
+    let mut result = Vec::new();
+    let mut push = |lexeme| result.extend(parser.push(lexeme));
+    lexer.feed(input.as_bytes(), &mut push);
+    lexer.finish(&mut push);
+    result
+}
+
+pub fn run(input: &str, set: &InstructionSet) -> State {
+    let mut machine = Machine::new(set);
+    for decoded in decode(input, set) {
+        machine.step(&decoded.instruction);
+    }
+    machine.state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instructions(input: &str, set: &InstructionSet) -> Vec<Instruction> {
+        decode(input, set)
+            .into_iter()
+            .map(|it| it.instruction)
+            .collect()
+    }
+
+    #[test]
+    fn should_decode_example() {
+        let given = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let expected = vec![
+            Instruction::Mul(2, 4),
+            Instruction::Dont,
+            Instruction::Mul(5, 5),
+            Instruction::Mul(11, 8),
+            Instruction::Do,
+            Instruction::Mul(8, 5),
+        ];
+        let actual = instructions(given, &InstructionSet::puzzle());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_reject_long_operands() {
+        let given = "mul(1234,5)mul(123,0045)mul(12,3 )mul( 1,2)mul(1,2,3)mul(007,1)";
+        let expected = vec![Instruction::Mul(7, 1)];
+        let actual = instructions(given, &InstructionSet::puzzle());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_restart_on_rejected_token() {
+        let given = "mul(mul(2,3)mul(4,mul(5,6)do(don't()";
+        let expected = vec![
+            Instruction::Mul(2, 3),
+            Instruction::Mul(5, 6),
+            Instruction::Dont,
+        ];
+        let actual = instructions(given, &InstructionSet::puzzle());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_span_instruction() {
+        let given = "xmul(2,4)%undo()";
+        let expected = vec![1..9, 12..16];
+        let actual: Vec<_> = decode(given, &InstructionSet::puzzle())
+            .into_iter()
+            .map(|it| it.span)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_keep_source_text() {
+        let given = "x\nmul(02,4)\n\n%undo()";
+        let expected = vec![("mul(02,4)".to_string(), 2), ("do()".to_string(), 4)];
+        let actual: Vec<_> = decode(given, &InstructionSet::puzzle())
+            .into_iter()
+            .map(|it| (it.text, it.line))
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_run_enabled_only() {
+        let given = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let actual = run(given, &InstructionSet::puzzle());
+        assert_eq!(actual.total, 48);
+        assert!(actual.enabled);
+    }
+
+    #[test]
+    fn should_ignore_unregistered_instructions() {
+        let given = "don't()mul(2,4)";
+        let set = InstructionSet::empty().register(Definition::mul());
+        let actual = run(given, &set);
+        assert_eq!(actual.total, 8);
+    }
+
+    #[test]
+    fn should_run_custom_instruction() {
+        fn add(state: &mut State, args: &[u32]) {
+            state.total += args.iter().map(|&a| a as u64).sum::<u64>();
+        }
+        let given = "add(1,2,3)don't()add(4,5,6)xmul(2,2)sub(1)";
+        let set = InstructionSet::puzzle().register(Definition::custom(
+            "add",
+            vec![Operand::digits(1..=1); 3],
+            add,
+            true,
+        ));
+        let actual = run(given, &set);
+        assert_eq!(actual.total, 6);
+    }
+
+    #[test]
+    fn should_prefer_longest_name() {
+        fn square(state: &mut State, args: &[u32]) {
+            state.total += args[0] as u64 * args[0] as u64;
+        }
+        let set = InstructionSet::puzzle().register(Definition::custom(
+            "smul",
+            vec![Operand::digits(1..=3)],
+            square,
+            true,
+        ));
+        let expected = vec![
+            Instruction::Custom {
+                name: "smul".into(),
+                args: vec![3],
+            },
+            Instruction::Mul(3, 4),
+        ];
+        let actual = instructions("smul(3)smul(3,4)", &set);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[should_panic(expected = "can never be lexed")]
+    fn should_reject_unlexable_name() {
+        fn noop(_: &mut State, _: &[u32]) {}
+        Definition::custom("add2", vec![], noop, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "can never be lexed")]
+    fn should_reject_name_past_word_limit() {
+        fn noop(_: &mut State, _: &[u32]) {}
+        Definition::custom(&"a".repeat(MAX_WORD_LEN + 1), vec![], noop, false);
+    }
+}
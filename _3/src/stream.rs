@@ -0,0 +1,130 @@
+use std::io::{self, ErrorKind, Read};
+
+use crate::{
+    lexer::Lexer,
+    program::{Decoded, InstructionSet, Machine, Parser, State},
+};
+
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+// Decodes everything `reader` yields while holding at most `chunk_size` bytes
+// of input; instructions split across two reads are still picked up since the
+// lexer and parser carry their state from one chunk to the next.
+pub fn decode_reader(
+    mut reader: impl Read,
+    set: &InstructionSet,
+    chunk_size: usize,
+    mut on_decoded: impl FnMut(Decoded),
+) -> io::Result<()> {
+    let mut lexer = Lexer::new();
+    let mut parser = Parser::new(set);
+    let mut buf = vec![0; chunk_size.max(1)];
+    let mut push = |lexeme| {
+        if let Some(decoded) = parser.push(lexeme) {
+            on_decoded(decoded);
+        }
+    };
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        lexer.feed(&buf[..n], &mut push);
+    }
+    lexer.finish(&mut push);
+
+    Ok(())
+}
+
+pub fn run_reader(reader: impl Read, set: &InstructionSet, chunk_size: usize) -> io::Result<State> {
+    let mut machine = Machine::new(set);
+    decode_reader(reader, set, chunk_size, |decoded| {
+        machine.step(&decoded.instruction);
+    })?;
+    Ok(machine.state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::{decode, run, Instruction};
+
+    const EXAMPLE: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    // hands out at most `step` bytes per read, then fails if `fail` is set
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+        fail: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() && self.fail {
+                return Err(io::Error::new(ErrorKind::UnexpectedEof, "disk went away"));
+            }
+            let n = self.step.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn should_match_in_memory_for_any_chunk_size() {
+        let set = InstructionSet::puzzle();
+        let expected = decode(EXAMPLE, &set);
+        for chunk_size in 1..=EXAMPLE.len() {
+            let mut actual = Vec::new();
+            decode_reader(EXAMPLE.as_bytes(), &set, chunk_size, |it| actual.push(it)).unwrap();
+            assert_eq!(expected, actual, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn should_handle_short_reads() {
+        let set = InstructionSet::puzzle();
+        for step in 1..8 {
+            let reader = Trickle {
+                data: EXAMPLE.as_bytes(),
+                step,
+                fail: false,
+            };
+            let actual = run_reader(reader, &set, CHUNK_SIZE).unwrap();
+            assert_eq!(actual, run(EXAMPLE, &set));
+        }
+    }
+
+    #[test]
+    fn should_carry_enable_state_between_chunks() {
+        let given = "don't()mul(2,3)do()mul(4,5)";
+        let actual = run_reader(given.as_bytes(), &InstructionSet::puzzle(), 7).unwrap();
+        assert_eq!(actual.total, 20);
+    }
+
+    #[test]
+    fn should_propagate_read_errors() {
+        let reader = Trickle {
+            data: b"mul(2,3)",
+            step: 3,
+            fail: true,
+        };
+        let actual = run_reader(reader, &InstructionSet::puzzle(), CHUNK_SIZE);
+        assert_eq!(actual.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn should_report_offsets_in_whole_stream() {
+        let given = "....................mul(1,2)";
+        let mut actual = Vec::new();
+        decode_reader(given.as_bytes(), &InstructionSet::puzzle(), 4, |it| {
+            actual.push((it.instruction, it.span))
+        })
+        .unwrap();
+        assert_eq!(actual, vec![(Instruction::Mul(1, 2), 20..28)]);
+    }
+}
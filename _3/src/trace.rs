@@ -0,0 +1,120 @@
+use std::{fmt, ops::Range};
+
+use crate::program::{decode, Instruction, InstructionSet, Machine, Outcome};
+
+const RESET: &str = "\x1b[0m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub span: Range<usize>,
+    pub line: usize,
+    pub text: String,
+    pub instruction: Instruction,
+    pub outcome: Outcome,
+    // running total after the step
+    pub total: u64,
+}
+
+impl Step {
+    fn colour(&self) -> &'static str {
+        match (&self.instruction, self.outcome) {
+            (Instruction::Do | Instruction::Dont, _) => YELLOW,
+            (_, Outcome::Executed) => GREEN,
+            (_, Outcome::Skipped) => RED,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self.outcome {
+            Outcome::Executed => "executed",
+            Outcome::Skipped => "skipped",
+        };
+        write!(
+            f,
+            "{:>8} {:>5}  {:<16} {:<8} {}",
+            self.span.start, self.line, self.text, outcome, self.total
+        )
+    }
+}
+
+pub fn trace(input: &str, set: &InstructionSet) -> Vec<Step> {
+    let mut machine = Machine::new(set);
+    decode(input, set)
+        .into_iter()
+        .map(|decoded| {
+            let outcome = machine.step(&decoded.instruction);
+            Step {
+                span: decoded.span,
+                line: decoded.line,
+                text: decoded.text,
+                instruction: decoded.instruction,
+                outcome,
+                total: machine.state.total,
+            }
+        })
+        .collect()
+}
+
+// Input with every traced instruction coloured: executed green, skipped red
+// and do/don't yellow. Steps must be sorted by offset, as `trace` returns them.
+pub fn highlight(input: &str, steps: &[Step]) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut last = 0;
+    for step in steps {
+        result.push_str(&input[last..step.span.start]);
+        result.push_str(step.colour());
+        result.push_str(&input[step.span.clone()]);
+        result.push_str(RESET);
+        last = step.span.end;
+    }
+    result.push_str(&input[last..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn should_trace_example() {
+        let actual = trace(EXAMPLE, &InstructionSet::puzzle());
+        let expected = vec![
+            (1, "mul(2,4)", Outcome::Executed, 8),
+            (20, "don't()", Outcome::Executed, 8),
+            (28, "mul(5,5)", Outcome::Skipped, 8),
+            (48, "mul(11,8)", Outcome::Skipped, 8),
+            (59, "do()", Outcome::Executed, 8),
+            (64, "mul(8,5)", Outcome::Executed, 48),
+        ];
+        let actual: Vec<_> = actual
+            .iter()
+            .map(|it| (it.span.start, it.text.as_str(), it.outcome, it.total))
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_highlight_spans() {
+        let given = "xmul(2,4)don't()?mul(1,1)";
+        let steps = trace(given, &InstructionSet::puzzle());
+        let expected =
+            format!("x{GREEN}mul(2,4){RESET}{YELLOW}don't(){RESET}?{RED}mul(1,1){RESET}");
+        let actual = highlight(given, &steps);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_display_step() {
+        let steps = trace("\n\n  mul(3,4)", &InstructionSet::puzzle());
+        let expected = "       4     3  mul(3,4)         executed 12";
+        assert_eq!(expected, steps[0].to_string());
+    }
+}
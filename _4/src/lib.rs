@@ -0,0 +1,3 @@
+pub mod report;
+pub mod search;
+pub mod template;
@@ -1,9 +1,18 @@
 use std::{
+    env,
     error::Error,
     fs::File,
     io::{BufRead, BufReader},
 };
 
+use _4::{
+    report::{render, Report},
+    search::{search, Match, Options},
+    template::{x_mas, Matcher, Placement, Symmetry},
+};
+
+const WORD: [&str; 1] = ["XMAS"];
+
 fn main() -> Result<(), Box<dyn Error>> {
     let f = File::open("puzzle.txt")?;
 
@@ -16,101 +25,49 @@ fn main() -> Result<(), Box<dyn Error>> {
         levels.push(line.chars().collect());
     }
 
-    let total_xmas = xmas_in_mat(levels.clone());
-    let total_x_mas = x_mas_in_mat(levels);
+    let xmas = xmas_in_mat(&levels);
+    let matcher = x_mas_matcher();
+    let x_mas = x_mas_in_mat(&matcher, &levels);
+
+    // --render draws the matched letters of each part, --json exports them
+    let mut part1 = Report::default();
+    part1.add_words(&levels, &WORD, &xmas);
+    let mut part2 = Report::default();
+    part2.add_templates(&matcher, &x_mas);
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--render" => {
+                println!("{}\n", render(&levels, &part1.cells()));
+                println!("{}\n", render(&levels, &part2.cells()));
+            }
+            "--json" => {
+                let both = Report {
+                    words: part1.words.clone(),
+                    templates: part2.templates.clone(),
+                };
+                println!("{}", both.to_json()?);
+            }
+            _ => return Err(format!("unknown argument {}", arg).into()),
+        }
+    }
 
-    println!("result 1: {}", total_xmas);
-    println!("result 2: {}", total_x_mas);
+    println!("result 1: {}", xmas.len());
+    println!("result 2: {}", x_mas.len());
 
     Ok(())
 }
 
-fn xmas_in_mat(given: Vec<Vec<char>>) -> u32 {
-    let word = vec!['X', 'M', 'A', 'S'];
-    let mut counter = 0;
-
-    let directions = vec![
-        (0, 1),   // east
-        (0, -1),  // west
-        (-1, 0),  // north
-        (1, 0),   // south
-        (-1, 1),  // northeast
-        (-1, -1), // northwest
-        (1, -1),  // southwest
-        (1, 1),   // northeast
-    ];
-
-    for i in 0..given.len() {
-        for j in 0..given[0].len() {
-            if given[i][j] == 'X' {
-                for (ro, co) in &directions {
-                    // row and col offset
-                    let mut match_found = true;
-
-                    for z in 1..word.len() {
-                        // new row and new col
-                        let nr = i as isize + z as isize * ro;
-                        let nc = j as isize + z as isize * co;
-
-                        // boundary check
-                        if nr < 0
-                            || nc < 0
-                            || nr >= given.len() as isize
-                            || nc >= given[0].len() as isize
-                        {
-                            match_found = false;
-                            break;
-                        }
-
-                        if given[nr as usize][nc as usize] != word[z] {
-                            match_found = false;
-                            break;
-                        }
-                    }
-
-                    if match_found {
-                        counter += 1;
-                    }
-                }
-            }
-        }
-    }
-
-    counter
+fn xmas_in_mat(given: &[Vec<char>]) -> Vec<Match> {
+    search(given, &WORD, &Options::default())
 }
 
-fn x_mas_in_mat(matrix: Vec<Vec<char>>) -> i32 {
-    let mut count = 0;
-
-    for i in 0..matrix.len() {
-        for j in 0..matrix[0].len() {
-            if matrix[i][j] == 'A' {
-                if i == 0 || i == matrix.len() - 1 || j == 0 || j == matrix[0].len() - 1 {
-                    continue;
-                }
-
-                let nw = matrix[i - 1][j - 1];
-                let ne = matrix[i - 1][j + 1];
-                let sw = matrix[i + 1][j + 1];
-                let se = matrix[i + 1][j - 1];
-
-                if nw == 'M' && sw == 'S' && ne == 'M' && se == 'S' {
-                    count += 1;
-                }
-                if nw == 'S' && sw == 'M' && ne == 'S' && se == 'M' {
-                    count += 1;
-                }
-                if nw == 'S' && sw == 'M' && ne == 'M' && se == 'S' {
-                    count += 1;
-                }
-                if nw == 'M' && sw == 'S' && ne == 'S' && se == 'M' {
-                    count += 1;
-                }
-            }
-        }
-    }
+fn x_mas_matcher() -> Matcher {
+    Matcher::new(&x_mas(), Symmetry::Rotations)
+}
 
-    count
+fn x_mas_in_mat(matcher: &Matcher, matrix: &[Vec<char>]) -> Vec<Placement> {
+    matcher.find(matrix)
 }
 
 #[cfg(test)]
@@ -130,7 +87,7 @@ XMAS.S
 .X....";
         let given = str_to_mat(xmas);
         let expected = 4;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -142,7 +99,7 @@ XMAS.S
 .X....";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -154,7 +111,7 @@ XMAS.S
 .X....";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -166,7 +123,7 @@ XMAS.S
 .X.....";
         let given = str_to_mat(xmas);
         let expected = 2;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -179,7 +136,7 @@ XMAS.S
 .X.....";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -192,7 +149,7 @@ XMAS.S
 .S.....";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -205,7 +162,7 @@ XMAS.S
 S......";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -218,7 +175,7 @@ S......";
 .S....S";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -231,7 +188,7 @@ S......";
 ...X...";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -244,7 +201,7 @@ S......";
 XS.....";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -263,7 +220,7 @@ MAMMMXMMMM
 MXMXAXMASX";
         let given = str_to_mat(xmas);
         let expected = 18;
-        let actual = xmas_in_mat(given);
+        let actual = xmas_in_mat(&given).len();
 
         assert_eq!(actual, expected);
     }
@@ -275,7 +232,7 @@ MXMXAXMASX";
 M.S";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = x_mas_in_mat(given);
+        let actual = x_mas_in_mat(&x_mas_matcher(), &given).len();
 
         assert_eq!(actual, expected);
     }
@@ -287,7 +244,7 @@ M.S";
 S.M";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = x_mas_in_mat(given);
+        let actual = x_mas_in_mat(&x_mas_matcher(), &given).len();
 
         assert_eq!(actual, expected);
     }
@@ -299,7 +256,7 @@ S.M";
 S.S";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = x_mas_in_mat(given);
+        let actual = x_mas_in_mat(&x_mas_matcher(), &given).len();
 
         assert_eq!(actual, expected);
     }
@@ -311,7 +268,7 @@ S.S";
 M.M";
         let given = str_to_mat(xmas);
         let expected = 1;
-        let actual = x_mas_in_mat(given);
+        let actual = x_mas_in_mat(&x_mas_matcher(), &given).len();
 
         assert_eq!(actual, expected);
     }
@@ -330,11 +287,8 @@ M.M.M.M.M.
 ..........";
         let given = str_to_mat(xmas);
         let expected = 9;
-        let actual = x_mas_in_mat(given);
+        let actual = x_mas_in_mat(&x_mas_matcher(), &given).len();
 
         assert_eq!(actual, expected);
     }
 }
-
-
-
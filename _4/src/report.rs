@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{
+    search::{Direction, Match},
+    template::{Matcher, Placement},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordMatch {
+    pub word: String,
+    pub start: (usize, usize),
+    pub direction: Direction,
+    pub len: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateMatch {
+    pub origin: (usize, usize),
+    pub variant: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub words: Vec<WordMatch>,
+    pub templates: Vec<TemplateMatch>,
+}
+
+impl Report {
+    pub fn add_words<S: AsRef<str>>(&mut self, grid: &[Vec<char>], words: &[S], matches: &[Match]) {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        self.words.extend(matches.iter().map(|m| WordMatch {
+            word: words[m.word].as_ref().to_string(),
+            start: m.start,
+            direction: m.direction,
+            len: m.len,
+            cells: m.cells(rows, cols),
+        }));
+    }
+
+    pub fn add_templates(&mut self, matcher: &Matcher, placements: &[Placement]) {
+        self.templates
+            .extend(placements.iter().map(|p| TemplateMatch {
+                origin: p.origin,
+                variant: p.variant,
+                cells: matcher.cells(p),
+            }));
+    }
+
+    pub fn cells(&self) -> HashSet<(usize, usize)> {
+        self.words
+            .iter()
+            .flat_map(|m| m.cells.iter())
+            .chain(self.templates.iter().flat_map(|m| m.cells.iter()))
+            .copied()
+            .collect()
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+// The grid as the puzzle statement draws it: matched letters are kept and
+// everything else becomes '.'.
+pub fn render(grid: &[Vec<char>], cells: &HashSet<(usize, usize)>) -> String {
+    grid.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, &ch)| if cells.contains(&(i, j)) { ch } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        search::{search, Options},
+        template::{x_mas, Symmetry},
+    };
+
+    fn str_to_mat(s: &str) -> Vec<Vec<char>> {
+        s.lines().map(|a| a.chars().collect()).collect()
+    }
+
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[test]
+    fn should_render_like_puzzle_statement() {
+        let grid = str_to_mat(EXAMPLE);
+        let mut report = Report::default();
+        report.add_words(
+            &grid,
+            &["XMAS"],
+            &search(&grid, &["XMAS"], &Options::default()),
+        );
+        let expected = "....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX";
+        assert_eq!(report.words.len(), 18);
+        assert_eq!(expected, render(&grid, &report.cells()));
+    }
+
+    #[test]
+    fn should_render_x_mas() {
+        let grid = str_to_mat(EXAMPLE);
+        let matcher = Matcher::new(&x_mas(), Symmetry::Rotations);
+        let mut report = Report::default();
+        report.add_templates(&matcher, &matcher.find(&grid));
+        let expected = ".M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........";
+        assert_eq!(report.templates.len(), 9);
+        assert_eq!(expected, render(&grid, &report.cells()));
+    }
+
+    #[test]
+    fn should_export_json() {
+        let grid = str_to_mat("XMAS");
+        let options = Options {
+            directions: vec![Direction::East],
+            ..Options::default()
+        };
+        let mut report = Report::default();
+        report.add_words(&grid, &["XMAS"], &search(&grid, &["XMAS"], &options));
+        let actual: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        let expected = serde_json::json!({
+            "words": [{
+                "word": "XMAS",
+                "start": [0, 0],
+                "direction": "East",
+                "len": 4,
+                "cells": [[0, 0], [0, 1], [0, 2], [0, 3]],
+            }],
+            "templates": [],
+        });
+        assert_eq!(expected, actual);
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    // row and col offset
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    // the grid is a torus, rays leaving one edge come back on the opposite one
+    pub wrap: bool,
+    pub directions: Vec<Direction>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            wrap: false,
+            directions: Direction::ALL.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Match {
+    // index into the searched word list
+    pub word: usize,
+    pub start: (usize, usize),
+    pub direction: Direction,
+    pub len: usize,
+}
+
+impl Match {
+    // cells covered by the word in reading order, wrapped into a rows x cols grid
+    pub fn cells(&self, rows: usize, cols: usize) -> Vec<(usize, usize)> {
+        let (ro, co) = self.direction.offset();
+        let (i, j) = self.start;
+        (0..self.len as isize)
+            .map(|z| {
+                (
+                    (i as isize + z * ro).rem_euclid(rows as isize) as usize,
+                    (j as isize + z * co).rem_euclid(cols as isize) as usize,
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: HashMap<char, usize>,
+    // words ending here, more than one when the list has duplicates
+    words: Vec<usize>,
+}
+
+// Prefix tree over the word list, walking a ray costs at most the longest
+// word regardless of how many words there are.
+#[derive(Debug)]
+pub struct Dictionary {
+    nodes: Vec<Node>,
+    lens: Vec<usize>,
+    max_len: usize,
+}
+
+impl Dictionary {
+    pub fn new<S: AsRef<str>>(words: &[S]) -> Self {
+        let mut nodes = vec![Node::default()];
+        let mut lens = Vec::new();
+
+        for (i, word) in words.iter().enumerate() {
+            let mut node = 0;
+            let mut len = 0;
+            for ch in word.as_ref().chars() {
+                node = match nodes[node].children.get(&ch) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].children.insert(ch, next);
+                        next
+                    }
+                };
+                len += 1;
+            }
+            // the empty word would match everywhere, skip it
+            if len > 0 {
+                nodes[node].words.push(i);
+            }
+            lens.push(len);
+        }
+
+        let max_len = lens.iter().copied().max().unwrap_or(0);
+        Dictionary {
+            nodes,
+            lens,
+            max_len,
+        }
+    }
+
+    pub fn search(&self, grid: &[Vec<char>], options: &Options) -> Vec<Match> {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        let mut result = Vec::new();
+
+        for i in 0..rows {
+            for j in 0..grid[i].len() {
+                // most cells can't start any word
+                if !self.nodes[0].children.contains_key(&grid[i][j]) {
+                    continue;
+                }
+                for (d, &direction) in options.directions.iter().enumerate() {
+                    let (ro, co) = direction.offset();
+                    let limit = if options.wrap {
+                        self.max_len.min(ray_period(rows, cols, ro, co))
+                    } else {
+                        self.max_len
+                    };
+
+                    let mut node = 0;
+                    for z in 0..limit as isize {
+                        // new row and new col
+                        let nr = i as isize + z * ro;
+                        let nc = j as isize + z * co;
+                        let (nr, nc) = if options.wrap {
+                            (nr.rem_euclid(rows as isize), nc.rem_euclid(cols as isize))
+                        } else if nr < 0 || nc < 0 || nr >= rows as isize || nc >= cols as isize {
+                            break;
+                        } else {
+                            (nr, nc)
+                        };
+
+                        // ragged rows just end the ray early
+                        let Some(&next) = grid[nr as usize]
+                            .get(nc as usize)
+                            .and_then(|ch| self.nodes[node].children.get(ch))
+                        else {
+                            break;
+                        };
+                        node = next;
+
+                        // a one letter word reads the same every way, it
+                        // is reported for the first direction only
+                        if z == 0 && d > 0 {
+                            continue;
+                        }
+                        result.extend(self.nodes[node].words.iter().map(|&word| Match {
+                            word,
+                            start: (i, j),
+                            direction,
+                            len: self.lens[word],
+                        }));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+// steps until a ray on a torus is back at its start, no cell is read twice
+fn ray_period(rows: usize, cols: usize, ro: isize, co: isize) -> usize {
+    let r = if ro == 0 { 1 } else { rows };
+    let c = if co == 0 { 1 } else { cols };
+    r / gcd(r, c) * c
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        let temp = b;
+        b = a % b;
+        a = temp;
+    }
+    a
+}
+
+pub fn search<S: AsRef<str>>(grid: &[Vec<char>], words: &[S], options: &Options) -> Vec<Match> {
+    Dictionary::new(words).search(grid, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str_to_mat(s: &str) -> Vec<Vec<char>> {
+        s.lines().map(|a| a.chars().collect()).collect()
+    }
+
+    #[test]
+    fn should_report_start_direction_and_len() {
+        let given = str_to_mat(
+            "..X...
+.SAMX.
+.A..A.
+XMAS.S
+.X....",
+        );
+        let mut actual = search(&given, &["XMAS"], &Options::default());
+        actual.sort();
+        let expected = vec![
+            Match {
+                word: 0,
+                start: (0, 2),
+                direction: Direction::SouthEast,
+                len: 4,
+            },
+            Match {
+                word: 0,
+                start: (1, 4),
+                direction: Direction::West,
+                len: 4,
+            },
+            Match {
+                word: 0,
+                start: (3, 0),
+                direction: Direction::East,
+                len: 4,
+            },
+            Match {
+                word: 0,
+                start: (4, 1),
+                direction: Direction::North,
+                len: 4,
+            },
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_find_many_words_and_prefixes() {
+        let given = str_to_mat("CATS\n....");
+        let words = ["CAT", "CATS", "AT", "DOG", "TAC"];
+        let options = Options {
+            directions: vec![Direction::East, Direction::West],
+            ..Options::default()
+        };
+        let mut actual: Vec<_> = search(&given, &words, &options)
+            .into_iter()
+            .map(|m| (m.word, m.start, m.direction))
+            .collect();
+        actual.sort();
+        let expected = vec![
+            (0, (0, 0), Direction::East),
+            (1, (0, 0), Direction::East),
+            (2, (0, 1), Direction::East),
+            (4, (0, 2), Direction::West),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_restrict_directions() {
+        let given = str_to_mat("XMAS\nSAMX");
+        let options = Options {
+            directions: vec![Direction::East],
+            ..Options::default()
+        };
+        let actual = search(&given, &["XMAS"], &options);
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].start, (0, 0));
+    }
+
+    #[test]
+    fn should_wrap_around_torus() {
+        let given = str_to_mat("AS..XM\n......");
+        let options = Options {
+            wrap: true,
+            ..Options::default()
+        };
+        let actual = search(&given, &["XMAS"], &options);
+        let expected = vec![Match {
+            word: 0,
+            start: (0, 4),
+            direction: Direction::East,
+            len: 4,
+        }];
+        assert_eq!(expected, actual);
+        assert!(search(&given, &["XMAS"], &Options::default()).is_empty());
+    }
+
+    #[test]
+    fn should_not_reuse_cells_on_torus() {
+        let given = str_to_mat("AB");
+        let options = Options {
+            wrap: true,
+            directions: vec![Direction::East],
+        };
+        assert!(search(&given, &["ABA"], &options).is_empty());
+        assert_eq!(search(&given, &["BA"], &options).len(), 1);
+    }
+
+    #[test]
+    fn should_list_cells_of_match() {
+        let given = Match {
+            word: 0,
+            start: (0, 3),
+            direction: Direction::SouthWest,
+            len: 3,
+        };
+        assert_eq!(given.cells(5, 5), vec![(0, 3), (1, 2), (2, 1)]);
+        let wrapped = Match {
+            direction: Direction::East,
+            ..given
+        };
+        assert_eq!(wrapped.cells(5, 5), vec![(0, 3), (0, 4), (0, 0)]);
+    }
+
+    #[test]
+    fn should_report_duplicate_words() {
+        let given = str_to_mat("XMAS");
+        let actual = search(&given, &["XMAS", "XMAS"], &Options::default());
+        assert_eq!(actual.len(), 2);
+    }
+
+    #[test]
+    fn should_report_single_letters_once() {
+        let given = str_to_mat("X.\n..");
+        let actual = search(&given, &["X"], &Options::default());
+        let expected = vec![Match {
+            word: 0,
+            start: (0, 0),
+            direction: Direction::North,
+            len: 1,
+        }];
+        assert_eq!(expected, actual);
+        let actual = search(&str_to_mat("XX"), &["X", "XX"], &Options::default());
+        // each X once, XX both ways
+        assert_eq!(actual.len(), 4);
+    }
+
+    #[test]
+    fn should_handle_empty_grid_and_words() {
+        let empty: Vec<Vec<char>> = Vec::new();
+        assert!(search(&empty, &["XMAS"], &Options::default()).is_empty());
+        assert!(search(&str_to_mat("X"), &[""], &Options::default()).is_empty());
+    }
+}
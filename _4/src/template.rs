@@ -0,0 +1,276 @@
+// A small pattern grid where None cells match anything.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Template {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Exact,
+    Rotations,
+    RotationsAndReflections,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Placement {
+    // top left cell of the template in the grid
+    pub origin: (usize, usize),
+    // index into Matcher::variants
+    pub variant: usize,
+}
+
+impl Template {
+    // shorter rows are padded with wildcards
+    pub fn new(mut cells: Vec<Vec<Option<char>>>) -> Self {
+        let cols = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in cells.iter_mut() {
+            row.resize(cols, None);
+        }
+        Template { cells }
+    }
+
+    pub fn parse(s: &str, wildcard: char) -> Self {
+        Template::new(
+            s.lines()
+                .map(|line| {
+                    line.chars()
+                        .map(|ch| if ch == wildcard { None } else { Some(ch) })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    pub fn cell(&self, r: usize, c: usize) -> Option<char> {
+        self.cells[r][c]
+    }
+
+    // quarter turn clockwise
+    pub fn rotated(&self) -> Template {
+        let (rows, cols) = (self.rows(), self.cols());
+        Template {
+            cells: (0..cols)
+                .map(|r| (0..rows).map(|c| self.cells[rows - 1 - c][r]).collect())
+                .collect(),
+        }
+    }
+
+    // mirrored left to right
+    pub fn reflected(&self) -> Template {
+        Template {
+            cells: self
+                .cells
+                .iter()
+                .map(|row| row.iter().rev().copied().collect())
+                .collect(),
+        }
+    }
+
+    // distinct orientations, so symmetric templates are not matched twice
+    pub fn variants(&self, symmetry: Symmetry) -> Vec<Template> {
+        let mut bases = vec![self.clone()];
+        if symmetry == Symmetry::RotationsAndReflections {
+            bases.push(self.reflected());
+        }
+        let turns = if symmetry == Symmetry::Exact { 1 } else { 4 };
+
+        let mut result: Vec<Template> = Vec::new();
+        for base in bases {
+            let mut current = base;
+            for _ in 0..turns {
+                let next = current.rotated();
+                if !result.contains(&current) {
+                    result.push(current);
+                }
+                current = next;
+            }
+        }
+        result
+    }
+
+    // non wildcard cells, relative to the top left corner
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.is_some())
+                    .map(move |(c, _)| (r, c))
+            })
+            .collect()
+    }
+
+    pub fn matches_at(&self, grid: &[Vec<char>], origin: (usize, usize)) -> bool {
+        let (i, j) = origin;
+        self.cells.iter().enumerate().all(|(r, row)| {
+            row.iter().enumerate().all(|(c, cell)| match cell {
+                None => true,
+                Some(ch) => grid.get(i + r).and_then(|line| line.get(j + c)) == Some(ch),
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    variants: Vec<Template>,
+}
+
+impl Matcher {
+    pub fn new(template: &Template, symmetry: Symmetry) -> Self {
+        Matcher {
+            variants: template.variants(symmetry),
+        }
+    }
+
+    pub fn variants(&self) -> &[Template] {
+        &self.variants
+    }
+
+    // grid cells a placement covers, wildcards excluded
+    pub fn cells(&self, placement: &Placement) -> Vec<(usize, usize)> {
+        let (i, j) = placement.origin;
+        self.variants[placement.variant]
+            .cells()
+            .into_iter()
+            .map(|(r, c)| (i + r, j + c))
+            .collect()
+    }
+
+    // every placement of every variant, overlapping ones included
+    pub fn find(&self, grid: &[Vec<char>]) -> Vec<Placement> {
+        let rows = grid.len();
+        let cols = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut result = Vec::new();
+
+        for (variant, template) in self.variants.iter().enumerate() {
+            let empty = template.rows() == 0 || template.cols() == 0;
+            if empty || template.rows() > rows || template.cols() > cols {
+                continue;
+            }
+            for i in 0..=rows - template.rows() {
+                for j in 0..=cols - template.cols() {
+                    if template.matches_at(grid, (i, j)) {
+                        result.push(Placement {
+                            origin: (i, j),
+                            variant,
+                        });
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+pub fn x_mas() -> Template {
+    Template::parse("M.S\n.A.\nM.S", '.')
+}
+
+pub fn plus_mas() -> Template {
+    Template::parse(".M.\nMAS\n.S.", '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str_to_mat(s: &str) -> Vec<Vec<char>> {
+        s.lines().map(|a| a.chars().collect()).collect()
+    }
+
+    #[test]
+    fn should_rotate_clockwise() {
+        let given = Template::parse("AB\nCD\nEF", '.');
+        let expected = Template::parse("ECA\nFDB", '.');
+        assert_eq!(expected, given.rotated());
+    }
+
+    #[test]
+    fn should_pad_ragged_templates() {
+        let given = Template::parse("ABC\nD", '.');
+        assert_eq!(given.cols(), 3);
+        assert_eq!(given.cell(1, 2), None);
+    }
+
+    #[test]
+    fn should_dedup_symmetric_variants() {
+        assert_eq!(x_mas().variants(Symmetry::Exact).len(), 1);
+        assert_eq!(x_mas().variants(Symmetry::Rotations).len(), 4);
+        assert_eq!(x_mas().variants(Symmetry::RotationsAndReflections).len(), 4);
+        let given = Template::parse("AB\nC.", '.');
+        assert_eq!(given.variants(Symmetry::RotationsAndReflections).len(), 8);
+    }
+
+    #[test]
+    fn should_match_all_x_mas() {
+        let given = str_to_mat(
+            ".M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........",
+        );
+        let actual = Matcher::new(&x_mas(), Symmetry::Rotations).find(&given);
+        assert_eq!(actual.len(), 9);
+    }
+
+    #[test]
+    fn should_report_overlapping_matches() {
+        let given = str_to_mat("M.M.M\n.A.A.\nS.S.S");
+        let mut actual = Matcher::new(&x_mas(), Symmetry::Rotations).find(&given);
+        actual.sort();
+        let origins: Vec<_> = actual.iter().map(|p| p.origin).collect();
+        assert_eq!(origins, vec![(0, 0), (0, 2)]);
+    }
+
+    #[test]
+    fn should_match_plus_mas() {
+        let given = str_to_mat(".M.\nMAS\n.S.\n...\n.S.\nSAM\n.M.");
+        let actual = Matcher::new(&plus_mas(), Symmetry::RotationsAndReflections).find(&given);
+        assert_eq!(actual.len(), 2);
+        let exact = Matcher::new(&plus_mas(), Symmetry::Exact).find(&given);
+        assert_eq!(
+            exact,
+            vec![Placement {
+                origin: (0, 0),
+                variant: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn should_list_cells_of_placement() {
+        let matcher = Matcher::new(&x_mas(), Symmetry::Exact);
+        let given = Placement {
+            origin: (2, 1),
+            variant: 0,
+        };
+        let expected = vec![(2, 1), (2, 3), (3, 2), (4, 1), (4, 3)];
+        assert_eq!(expected, matcher.cells(&given));
+    }
+
+    #[test]
+    fn should_skip_templates_larger_than_grid() {
+        let given = str_to_mat("MA\nAS");
+        assert!(Matcher::new(&x_mas(), Symmetry::Rotations)
+            .find(&given)
+            .is_empty());
+    }
+}
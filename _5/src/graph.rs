@@ -0,0 +1,180 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    error::Error,
+    fmt,
+};
+
+// Rules that can't all hold at once, `cycle[i]` must come before `cycle[i + 1]`
+// and the last page before the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub cycle: Vec<i32>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pages: Vec<String> = self.cycle.iter().map(|p| p.to_string()).collect();
+        write!(f, "contradictory rules: {}|{}", pages.join("|"), pages[0])
+    }
+}
+
+impl Error for CycleError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sorted {
+    pub order: Vec<i32>,
+    // false when some pages could be swapped without breaking a rule
+    pub unique: bool,
+}
+
+// Kahn's algorithm restricted to the pages in `arr`, ties are broken by the
+// order pages appear in `arr`.
+pub fn topological_order(
+    graph: &HashMap<i32, Vec<i32>>,
+    arr: &[i32],
+) -> Result<Sorted, CycleError> {
+    let pages: HashSet<i32> = arr.iter().copied().collect();
+    let mut in_degree: HashMap<i32, usize> = arr.iter().map(|&it| (it, 0)).collect();
+    let mut adj_list: HashMap<i32, Vec<i32>> = HashMap::new();
+
+    for &u in &pages {
+        let Some(vvs) = graph.get(&u) else { continue };
+        for &v in vvs.iter().filter(|v| pages.contains(v)) {
+            adj_list.entry(u).or_default().push(v);
+            *in_degree.entry(v).or_default() += 1;
+        }
+    }
+    let position: HashMap<i32, usize> = arr.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    for vvs in adj_list.values_mut() {
+        vvs.sort_by_key(|v| position[v]);
+    }
+
+    let mut q: VecDeque<i32> = VecDeque::new();
+    let mut queued = HashSet::new();
+    for &n in arr {
+        if in_degree[&n] == 0 && queued.insert(n) {
+            q.push_back(n);
+        }
+    }
+
+    let mut sorted_order = Vec::new();
+    let mut unique = true;
+
+    while let Some(n) = q.pop_front() {
+        if !q.is_empty() {
+            unique = false;
+        }
+        sorted_order.push(n);
+
+        if let Some(vvs) = adj_list.get(&n) {
+            for &v in vvs.iter() {
+                if let Some(in_deg) = in_degree.get_mut(&v) {
+                    *in_deg -= 1;
+                    if *in_deg == 0 {
+                        q.push_back(v)
+                    }
+                }
+            }
+        }
+    }
+
+    if sorted_order.len() < in_degree.len() {
+        let remaining: HashSet<i32> = in_degree
+            .into_iter()
+            .filter(|&(_, d)| d > 0)
+            .map(|(n, _)| n)
+            .collect();
+        return Err(CycleError {
+            cycle: find_cycle(&adj_list, &remaining),
+        });
+    }
+
+    Ok(Sorted {
+        order: sorted_order,
+        unique,
+    })
+}
+
+pub fn topological_sort(
+    graph: &HashMap<i32, Vec<i32>>,
+    arr: &[i32],
+) -> Result<Vec<i32>, CycleError> {
+    topological_order(graph, arr).map(|sorted| sorted.order)
+}
+
+// Every page Kahn's algorithm leaves behind still has a predecessor that was
+// left behind too, so walking predecessors must run into a cycle.
+fn find_cycle(adj_list: &HashMap<i32, Vec<i32>>, remaining: &HashSet<i32>) -> Vec<i32> {
+    let mut predecessor: HashMap<i32, i32> = HashMap::new();
+    for (&u, vvs) in adj_list {
+        if !remaining.contains(&u) {
+            continue;
+        }
+        for &v in vvs.iter().filter(|v| remaining.contains(v)) {
+            predecessor.entry(v).or_insert(u);
+        }
+    }
+
+    let Some(mut current) = remaining.iter().min().copied() else {
+        return Vec::new();
+    };
+    let mut seen = Vec::new();
+    while !seen.contains(&current) {
+        seen.push(current);
+        current = predecessor[&current];
+    }
+
+    let start = seen.iter().position(|&n| n == current).unwrap();
+    let mut cycle = seen.split_off(start);
+    cycle.reverse();
+    // start at the smallest page so the output is stable
+    let smallest = cycle.iter().enumerate().min_by_key(|&(_, n)| n).unwrap().0;
+    cycle.rotate_left(smallest);
+    cycle
+}
+
+pub fn to_graph(rules: &[(i32, i32)]) -> HashMap<i32, Vec<i32>> {
+    let mut dg: HashMap<i32, Vec<i32>> = HashMap::new();
+    for &(k, v) in rules {
+        dg.entry(k).or_default().push(v);
+    }
+    dg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_name_cycle() {
+        let graph = to_graph(&[(1, 2), (2, 3), (3, 4), (4, 2), (5, 1)]);
+        let actual = topological_sort(&graph, &[5, 4, 3, 2, 1]).unwrap_err();
+        assert_eq!(actual.cycle, vec![2, 3, 4]);
+        assert_eq!(actual.to_string(), "contradictory rules: 2|3|4|2");
+    }
+
+    #[test]
+    fn should_find_two_page_conflict() {
+        let graph = to_graph(&[(7, 3), (3, 7)]);
+        let actual = topological_sort(&graph, &[3, 7]).unwrap_err();
+        assert_eq!(actual.cycle, vec![3, 7]);
+    }
+
+    #[test]
+    fn should_ignore_cycles_outside_update() {
+        let graph = to_graph(&[(1, 2), (2, 3), (3, 1)]);
+        let actual = topological_sort(&graph, &[3, 2]).unwrap();
+        assert_eq!(actual, vec![2, 3]);
+    }
+
+    #[test]
+    fn should_detect_ambiguous_order() {
+        let graph = to_graph(&[(1, 2), (1, 3)]);
+        let actual = topological_order(&graph, &[3, 2, 1]).unwrap();
+        assert_eq!(actual.order, vec![1, 3, 2]);
+        assert!(!actual.unique);
+
+        let graph = to_graph(&[(1, 2), (1, 3), (2, 3)]);
+        assert!(topological_order(&graph, &[3, 2, 1]).unwrap().unique);
+    }
+}
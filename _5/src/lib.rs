@@ -0,0 +1,5 @@
+pub mod graph;
+pub mod parse;
+pub mod rules;
+pub mod validate;
+pub mod violations;
@@ -0,0 +1,238 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // every problem is reported, nothing is parsed unless the input is clean
+    Strict,
+    // malformed rules and page numbers are dropped silently
+    Lenient,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Puzzle {
+    pub rules: Vec<(i32, i32)>,
+    pub updates: Vec<Vec<i32>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    MalformedRule(String),
+    DuplicateRule { rule: (i32, i32), first_line: usize },
+    // the opposite rule was given on `first_line`
+    ConflictingRule { rule: (i32, i32), first_line: usize },
+    MalformedUpdate(String),
+    RepeatedPage(i32),
+    // an update with an even number of pages has no middle page
+    EvenLength(usize),
+}
+
+// 1-based line number and what is wrong with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub kind: DiagnosticKind,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            DiagnosticKind::MalformedRule(s) => write!(f, "malformed rule `{}`", s),
+            DiagnosticKind::DuplicateRule {
+                rule: (a, b),
+                first_line,
+            } => write!(f, "rule {}|{} already given on line {}", a, b, first_line),
+            DiagnosticKind::ConflictingRule {
+                rule: (a, b),
+                first_line,
+            } => write!(
+                f,
+                "rule {}|{} conflicts with {}|{} on line {}",
+                a, b, b, a, first_line
+            ),
+            DiagnosticKind::MalformedUpdate(s) => write!(f, "malformed update `{}`", s),
+            DiagnosticKind::RepeatedPage(page) => write!(f, "page {} appears more than once", page),
+            DiagnosticKind::EvenLength(len) => {
+                write!(f, "update has {} pages and no middle page", len)
+            }
+        }
+    }
+}
+
+impl Error for Diagnostic {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.diagnostics.iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Error for ParseError {}
+
+fn parse_rule(content: &str) -> Option<(i32, i32)> {
+    let (a, b) = content.split_once('|')?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+// Rules come first, one X|Y per line, then a blank line and one comma
+// separated update per line.
+pub fn parse(input: &str, mode: Mode) -> Result<Puzzle, ParseError> {
+    let mut puzzle = Puzzle::default();
+    let mut diagnostics = Vec::new();
+    let mut seen: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut is_rules_section = true;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let content = line.trim();
+        let mut report = |kind| {
+            diagnostics.push(Diagnostic {
+                line: line_no,
+                kind,
+            })
+        };
+
+        if content.is_empty() {
+            is_rules_section = false;
+            continue;
+        }
+
+        if is_rules_section {
+            let Some(rule) = parse_rule(content) else {
+                report(DiagnosticKind::MalformedRule(content.to_string()));
+                continue;
+            };
+            let (a, b) = rule;
+            if let Some(&first_line) = seen.get(&rule) {
+                report(DiagnosticKind::DuplicateRule { rule, first_line });
+            } else if let Some(&first_line) = seen.get(&(b, a)) {
+                report(DiagnosticKind::ConflictingRule { rule, first_line });
+            }
+            seen.entry(rule).or_insert(line_no);
+            puzzle.rules.push(rule);
+        } else {
+            let mut array = Vec::new();
+            let mut malformed = false;
+            for x in content.split(',') {
+                match x.trim().parse::<i32>() {
+                    Ok(page) => array.push(page),
+                    Err(_) => malformed = true,
+                }
+            }
+            if malformed {
+                report(DiagnosticKind::MalformedUpdate(content.to_string()));
+            }
+            let mut pages = HashMap::new();
+            for &page in &array {
+                *pages.entry(page).or_insert(0) += 1;
+                if pages[&page] == 2 {
+                    report(DiagnosticKind::RepeatedPage(page));
+                }
+            }
+            if !malformed && array.len() % 2 == 0 {
+                report(DiagnosticKind::EvenLength(array.len()));
+            }
+            puzzle.updates.push(array);
+        }
+    }
+
+    if mode == Mode::Strict && !diagnostics.is_empty() {
+        return Err(ParseError { diagnostics });
+    }
+    puzzle.updates.retain(|arr| !arr.is_empty());
+    Ok(puzzle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(usize, DiagnosticKind)> {
+        parse(input, Mode::Strict)
+            .unwrap_err()
+            .diagnostics
+            .into_iter()
+            .map(|d| (d.line, d.kind))
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_clean_input() {
+        let given = "47|53\n97|13\n\n75,47,61\n97\n";
+        let expected = Puzzle {
+            rules: vec![(47, 53), (97, 13)],
+            updates: vec![vec![75, 47, 61], vec![97]],
+        };
+        assert_eq!(expected, parse(given, Mode::Strict).unwrap());
+    }
+
+    #[test]
+    fn should_report_malformed_rules() {
+        let given = "47|53\n97-13\n|4\n1|x\n\n1,2,3";
+        let expected = vec![
+            (2, DiagnosticKind::MalformedRule("97-13".into())),
+            (3, DiagnosticKind::MalformedRule("|4".into())),
+            (4, DiagnosticKind::MalformedRule("1|x".into())),
+        ];
+        assert_eq!(expected, kinds(given));
+    }
+
+    #[test]
+    fn should_report_duplicate_and_conflicting_rules() {
+        let given = "1|2\n3|4\n1|2\n2|1\n\n1,2,3";
+        let expected = vec![
+            (
+                3,
+                DiagnosticKind::DuplicateRule {
+                    rule: (1, 2),
+                    first_line: 1,
+                },
+            ),
+            (
+                4,
+                DiagnosticKind::ConflictingRule {
+                    rule: (2, 1),
+                    first_line: 1,
+                },
+            ),
+        ];
+        assert_eq!(expected, kinds(given));
+    }
+
+    #[test]
+    fn should_report_bad_updates() {
+        let given = "1|2\n\n1,2,1\n1,2\n3,,4,5,x";
+        let expected = vec![
+            (3, DiagnosticKind::RepeatedPage(1)),
+            (4, DiagnosticKind::EvenLength(2)),
+            (5, DiagnosticKind::MalformedUpdate("3,,4,5,x".into())),
+        ];
+        assert_eq!(expected, kinds(given));
+    }
+
+    #[test]
+    fn should_describe_diagnostics() {
+        let actual = parse("1|2\n2|1\n\n1,2", Mode::Strict)
+            .unwrap_err()
+            .to_string();
+        let expected = "line 2: rule 2|1 conflicts with 1|2 on line 1
+line 4: update has 2 pages and no middle page";
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_skip_problems_when_lenient() {
+        let given = "47|53\nnope\n\n75,x,61\n\n97,13";
+        let expected = Puzzle {
+            rules: vec![(47, 53)],
+            updates: vec![vec![75, 61], vec![97, 13]],
+        };
+        assert_eq!(expected, parse(given, Mode::Lenient).unwrap());
+    }
+}
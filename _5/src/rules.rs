@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{to_graph, topological_order, CycleError, Sorted};
+
+// The X|Y rules indexed once, every query on an update only looks pairs up.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pairs: HashSet<(i32, i32)>,
+    graph: HashMap<i32, Vec<i32>>,
+}
+
+impl RuleSet {
+    pub fn new(rules: &[(i32, i32)]) -> Self {
+        let pairs: HashSet<(i32, i32)> = rules.iter().copied().collect();
+        let mut unique: Vec<(i32, i32)> = pairs.iter().copied().collect();
+        unique.sort();
+        RuleSet {
+            graph: to_graph(&unique),
+            pairs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn precedes(&self, a: i32, b: i32) -> bool {
+        self.pairs.contains(&(a, b))
+    }
+
+    // pairs with both a|b and b|a, smaller page first
+    pub fn conflicts(&self) -> Vec<(i32, i32)> {
+        let mut result: Vec<(i32, i32)> = self
+            .pairs
+            .iter()
+            .filter(|&&(a, b)| a < b && self.precedes(b, a))
+            .copied()
+            .collect();
+        result.sort();
+        result
+    }
+
+    pub fn is_ordered(&self, update: &[i32]) -> bool {
+        update
+            .iter()
+            .enumerate()
+            .all(|(i, &a)| update[i + 1..].iter().all(|&b| !self.precedes(b, a)))
+    }
+
+    // how many pages of the update each page has to follow
+    fn ranks(&self, update: &[i32]) -> Vec<usize> {
+        update
+            .iter()
+            .map(|&b| update.iter().filter(|&&a| self.precedes(a, b)).count())
+            .collect()
+    }
+
+    // The rules pin down a single order when every two pages have exactly
+    // one rule between them and the ranks are 0..n. A complete set of rules
+    // with those ranks has no cycle, so the comparator is a strict total
+    // order then and sorting by it is safe.
+    fn is_total_order(&self, update: &[i32]) -> bool {
+        let complete = update.iter().enumerate().all(|(i, &a)| {
+            update[i + 1..]
+                .iter()
+                .all(|&b| self.precedes(a, b) != self.precedes(b, a))
+        });
+        complete && is_permutation(self.ranks(update))
+    }
+
+    pub fn order(&self, update: &[i32]) -> Result<Sorted, CycleError> {
+        if self.is_total_order(update) {
+            let mut order = update.to_vec();
+            order.sort_by(|&a, &b| {
+                if self.precedes(a, b) {
+                    std::cmp::Ordering::Less
+                } else {
+                    std::cmp::Ordering::Greater
+                }
+            });
+            return Ok(Sorted {
+                order,
+                unique: true,
+            });
+        }
+        topological_order(&self.graph, update)
+    }
+
+    pub fn sort_update(&self, update: &[i32]) -> Result<Vec<i32>, CycleError> {
+        self.order(update).map(|sorted| sorted.order)
+    }
+
+    // Middle page of the sorted update without sorting it: the page that has
+    // to follow exactly half of the others. None when no single page does.
+    pub fn middle_page(&self, update: &[i32]) -> Option<i32> {
+        if !self.is_total_order(update) {
+            return None;
+        }
+        let mid = update.len() / 2;
+        let ranks = self.ranks(update);
+        update
+            .iter()
+            .zip(&ranks)
+            .find(|&(_, &r)| r == mid)
+            .map(|(&page, _)| page)
+    }
+}
+
+fn is_permutation(mut ranks: Vec<usize>) -> bool {
+    ranks.sort_unstable();
+    ranks.iter().enumerate().all(|(i, &r)| i == r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> RuleSet {
+        RuleSet::new(&[
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (97, 47),
+            (75, 29),
+            (61, 13),
+            (75, 53),
+            (29, 13),
+            (97, 29),
+            (53, 29),
+            (61, 53),
+            (97, 53),
+            (61, 29),
+            (47, 13),
+            (75, 47),
+            (97, 75),
+            (47, 61),
+            (75, 61),
+            (47, 29),
+            (75, 13),
+            (53, 13),
+        ])
+    }
+
+    #[test]
+    fn should_look_up_rules() {
+        let rules = example();
+        assert!(rules.precedes(47, 53));
+        assert!(!rules.precedes(53, 47));
+        assert!(!rules.precedes(47, 47));
+        assert_eq!(rules.len(), 21);
+    }
+
+    #[test]
+    fn should_check_order() {
+        let rules = example();
+        assert!(rules.is_ordered(&[75, 47, 61, 53, 29]));
+        assert!(!rules.is_ordered(&[61, 13, 29]));
+        assert!(!rules.is_ordered(&[97, 13, 75, 29, 47]));
+    }
+
+    #[test]
+    fn should_sort_updates() {
+        let rules = example();
+        assert_eq!(
+            rules.sort_update(&[75, 97, 47, 61, 53]).unwrap(),
+            vec![97, 75, 47, 61, 53]
+        );
+        assert_eq!(rules.sort_update(&[61, 13, 29]).unwrap(), vec![61, 29, 13]);
+        assert_eq!(
+            rules.sort_update(&[97, 13, 75, 29, 47]).unwrap(),
+            vec![97, 75, 47, 29, 13]
+        );
+    }
+
+    #[test]
+    fn should_find_middle_page_without_sorting() {
+        let rules = example();
+        assert_eq!(rules.middle_page(&[75, 97, 47, 61, 53]), Some(47));
+        assert_eq!(rules.middle_page(&[61, 13, 29]), Some(29));
+        assert_eq!(rules.middle_page(&[97, 13, 75, 29, 47]), Some(47));
+    }
+
+    #[test]
+    fn should_fall_back_to_topological_sort() {
+        let rules = RuleSet::new(&[(1, 2), (2, 3), (3, 1), (4, 5)]);
+        assert_eq!(
+            rules.sort_update(&[3, 2, 1]).unwrap_err().cycle,
+            vec![1, 2, 3]
+        );
+        assert_eq!(rules.middle_page(&[3, 2, 1]), None);
+
+        let actual = rules.order(&[6, 5, 4]).unwrap();
+        assert_eq!(actual.order, vec![6, 4, 5]);
+        assert!(!actual.unique);
+        assert_eq!(rules.middle_page(&[6, 5, 4]), None);
+    }
+
+    #[test]
+    fn should_not_take_cycles_for_an_order() {
+        // ranks 0..n but 1|3 and 3|1 both hold
+        let rules = RuleSet::new(&[(1, 2), (2, 3), (3, 1), (4, 2), (4, 3), (1, 3)]);
+        assert!(rules.order(&[1, 2, 3, 4]).is_err());
+        assert_eq!(rules.middle_page(&[1, 2, 3, 4]), None);
+
+        // ranks 0..n but nothing between 1 and 3
+        let rules = RuleSet::new(&[(1, 2), (2, 1), (3, 2)]);
+        assert_eq!(rules.order(&[1, 2, 3]).unwrap_err().cycle, vec![1, 2]);
+        assert_eq!(rules.middle_page(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn should_list_conflicts() {
+        let rules = RuleSet::new(&[(2, 1), (1, 2), (3, 4), (1, 2)]);
+        assert_eq!(rules.conflicts(), vec![(1, 2)]);
+        assert_eq!(rules.len(), 3);
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::{graph::CycleError, rules::RuleSet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    // both a|b and b|a are present
+    Conflict { a: i32, b: i32 },
+    // the rules between the pages of an update form a cycle
+    Cycle { update: usize, error: CycleError },
+    // more than one order of the update satisfies every rule
+    Ambiguous { update: usize },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Conflict { a, b } => write!(f, "rules {}|{} and {}|{} conflict", a, b, b, a),
+            Issue::Cycle { update, error } => write!(f, "update {}: {}", update + 1, error),
+            Issue::Ambiguous { update } => {
+                write!(f, "update {}: more than one valid order", update + 1)
+            }
+        }
+    }
+}
+
+// Rules only apply between pages of the same update, so a cycle through the
+// whole rule set is fine as long as no single update contains all of it.
+pub fn validate(rules: &RuleSet, updates: &[Vec<i32>]) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = rules
+        .conflicts()
+        .into_iter()
+        .map(|(a, b)| Issue::Conflict { a, b })
+        .collect();
+
+    for (update, arr) in updates.iter().enumerate() {
+        match rules.order(arr) {
+            Err(error) => issues.push(Issue::Cycle { update, error }),
+            Ok(sorted) if !sorted.unique => issues.push(Issue::Ambiguous { update }),
+            Ok(_) => {}
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_report_every_issue() {
+        let rules = RuleSet::new(&[(1, 2), (2, 1), (3, 4), (4, 5), (5, 3), (6, 7)]);
+        let updates = [vec![1, 2], vec![3, 4, 5], vec![6, 7], vec![6, 7, 8]];
+        let expected = vec![
+            Issue::Conflict { a: 1, b: 2 },
+            Issue::Cycle {
+                update: 0,
+                error: CycleError { cycle: vec![1, 2] },
+            },
+            Issue::Cycle {
+                update: 1,
+                error: CycleError {
+                    cycle: vec![3, 4, 5],
+                },
+            },
+            Issue::Ambiguous { update: 3 },
+        ];
+        assert_eq!(expected, validate(&rules, &updates));
+    }
+
+    #[test]
+    fn should_report_cycle_behind_conflict() {
+        let rules = RuleSet::new(&[(1, 2), (2, 1), (3, 2)]);
+        let expected = vec![
+            Issue::Conflict { a: 1, b: 2 },
+            Issue::Cycle {
+                update: 0,
+                error: CycleError { cycle: vec![1, 2] },
+            },
+        ];
+        assert_eq!(expected, validate(&rules, &[vec![1, 2, 3]]));
+    }
+
+    #[test]
+    fn should_describe_issues() {
+        let issue = Issue::Cycle {
+            update: 1,
+            error: CycleError {
+                cycle: vec![3, 4, 5],
+            },
+        };
+        assert_eq!(issue.to_string(), "update 2: contradictory rules: 3|4|5|3");
+    }
+}
@@ -0,0 +1,251 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{graph::CycleError, rules::RuleSet};
+
+// The rule X|Y is broken because Y sits at `after` before X at `before`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: (i32, i32),
+    pub before: usize,
+    pub after: usize,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x, y) = self.rule;
+        write!(
+            f,
+            "{}|{} broken: {} at position {} comes before {} at position {}",
+            x,
+            y,
+            y,
+            self.after + 1,
+            x,
+            self.before + 1
+        )
+    }
+}
+
+// Take `page` out of position `from` and put it back so it ends up at `to`,
+// positions are taken on the update as left by the previous moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub page: i32,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from position {} to position {}",
+            self.page,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub violations: Vec<Violation>,
+    pub moves: Vec<Move>,
+}
+
+pub fn violations(rules: &RuleSet, update: &[i32]) -> Vec<Violation> {
+    let mut result = Vec::new();
+    for (after, &y) in update.iter().enumerate() {
+        for (before, &x) in update.iter().enumerate().skip(after + 1) {
+            if rules.precedes(x, y) {
+                result.push(Violation {
+                    rule: (x, y),
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+    result
+}
+
+// Fewest single page moves that turn `update` into the order `sort_update`
+// returns: every page outside a longest run already in that order has to
+// move, everything inside it can stay.
+pub fn fixing_moves(rules: &RuleSet, update: &[i32]) -> Result<Vec<Move>, CycleError> {
+    let target = rules.sort_update(update)?;
+    let rank: HashMap<i32, usize> = target.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+    let ranks: Vec<usize> = update.iter().map(|p| rank[p]).collect();
+
+    let mut settled = vec![false; target.len()];
+    for i in longest_increasing(&ranks) {
+        settled[ranks[i]] = true;
+    }
+
+    let mut current = update.to_vec();
+    let mut moves = Vec::new();
+    for (r, &page) in target.iter().enumerate() {
+        if settled[r] {
+            continue;
+        }
+        let from = current.iter().position(|&p| p == page).unwrap();
+        current.remove(from);
+        let to = current
+            .iter()
+            .position(|p| settled[rank[p]] && rank[p] > r)
+            .unwrap_or(current.len());
+        current.insert(to, page);
+        settled[r] = true;
+        moves.push(Move { page, from, to });
+    }
+
+    Ok(moves)
+}
+
+// indices of a longest strictly increasing subsequence
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // tails[k] is the index ending the best run of length k + 1 found so far
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; values.len()];
+
+    for (i, &v) in values.iter().enumerate() {
+        let k = tails.partition_point(|&t| values[t] < v);
+        prev[i] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        result.push(i);
+        current = prev[i];
+    }
+    result.reverse();
+    result
+}
+
+pub fn explain(rules: &RuleSet, update: &[i32]) -> Result<Explanation, CycleError> {
+    Ok(Explanation {
+        violations: violations(rules, update),
+        moves: fixing_moves(rules, update)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> RuleSet {
+        RuleSet::new(&[
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (97, 47),
+            (75, 29),
+            (61, 13),
+            (75, 53),
+            (29, 13),
+            (97, 29),
+            (53, 29),
+            (61, 53),
+            (97, 53),
+            (61, 29),
+            (47, 13),
+            (75, 47),
+            (97, 75),
+            (47, 61),
+            (75, 61),
+            (47, 29),
+            (75, 13),
+            (53, 13),
+        ])
+    }
+
+    fn apply(update: &[i32], moves: &[Move]) -> Vec<i32> {
+        let mut result = update.to_vec();
+        for m in moves {
+            assert_eq!(result.remove(m.from), m.page);
+            result.insert(m.to, m.page);
+        }
+        result
+    }
+
+    #[test]
+    fn should_list_broken_rules() {
+        let actual = violations(&example(), &[75, 97, 47, 61, 53]);
+        let expected = vec![Violation {
+            rule: (97, 75),
+            before: 1,
+            after: 0,
+        }];
+        assert_eq!(expected, actual);
+        assert_eq!(
+            actual[0].to_string(),
+            "97|75 broken: 75 at position 1 comes before 97 at position 2"
+        );
+    }
+
+    #[test]
+    fn should_not_report_ordered_updates() {
+        let rules = example();
+        let actual = explain(&rules, &[75, 47, 61, 53, 29]).unwrap();
+        assert!(actual.violations.is_empty());
+        assert!(actual.moves.is_empty());
+    }
+
+    #[test]
+    fn should_fix_with_fewest_moves() {
+        let rules = example();
+        let given = [97, 13, 75, 29, 47];
+        let actual = fixing_moves(&rules, &given).unwrap();
+        // 97 75 47 can stay, 29 and 13 have to move
+        assert_eq!(
+            actual,
+            vec![
+                Move {
+                    page: 29,
+                    from: 3,
+                    to: 4
+                },
+                Move {
+                    page: 13,
+                    from: 1,
+                    to: 4
+                },
+            ]
+        );
+        assert_eq!(apply(&given, &actual), vec![97, 75, 47, 29, 13]);
+        assert_eq!(violations(&rules, &given).len(), 4);
+    }
+
+    #[test]
+    fn should_fix_every_example() {
+        let rules = example();
+        for given in [
+            vec![75, 97, 47, 61, 53],
+            vec![61, 13, 29],
+            vec![29, 13, 61, 53, 47, 97, 75],
+        ] {
+            let moves = fixing_moves(&rules, &given).unwrap();
+            assert!(rules.is_ordered(&apply(&given, &moves)));
+        }
+        let reversed = [13, 29, 53, 61, 47, 75, 97];
+        assert_eq!(fixing_moves(&rules, &reversed).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn should_find_longest_increasing_run() {
+        assert_eq!(longest_increasing(&[3, 0, 1, 4, 2]), vec![1, 2, 4]);
+        assert!(longest_increasing(&[]).is_empty());
+    }
+
+    #[test]
+    fn should_propagate_cycles() {
+        let rules = RuleSet::new(&[(1, 2), (2, 1)]);
+        assert!(explain(&rules, &[1, 2]).is_err());
+    }
+}
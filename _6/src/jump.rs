@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+
+use crate::puzzle::{Direction, Puzzle};
+
+const EXIT: u32 = u32::MAX;
+
+// For every cell and heading, the cell where the guard stops in front of the
+// next obstacle, or EXIT when it walks off the map first. A whole straight
+// run of the walk is one lookup.
+#[derive(Debug, Clone)]
+pub struct JumpTable {
+    cols: usize,
+    stops: [Vec<u32>; 4],
+}
+
+impl JumpTable {
+    pub fn new(rows: usize, cols: usize, objects: &HashSet<(usize, usize)>) -> Self {
+        let mut table = JumpTable {
+            cols,
+            stops: std::array::from_fn(|_| vec![EXIT; rows * cols]),
+        };
+        // each line is scanned from the side the guard is heading to, so the
+        // last obstacle seen is the next one it runs into
+        for y in 0..cols {
+            table.scan(Direction::Up, (0..rows).map(|x| (x, y)), objects);
+            table.scan(Direction::Down, (0..rows).rev().map(|x| (x, y)), objects);
+        }
+        for x in 0..rows {
+            table.scan(Direction::Left, (0..cols).map(|y| (x, y)), objects);
+            table.scan(Direction::Right, (0..cols).rev().map(|y| (x, y)), objects);
+        }
+        table
+    }
+
+    fn scan(
+        &mut self,
+        dir: Direction,
+        line: impl Iterator<Item = (usize, usize)>,
+        objects: &HashSet<(usize, usize)>,
+    ) {
+        let mut stop = EXIT;
+        let mut after_object = false;
+        for pt in line {
+            let i = self.index(pt);
+            if objects.contains(&pt) {
+                after_object = true;
+                continue;
+            }
+            if after_object {
+                stop = i as u32;
+                after_object = false;
+            }
+            self.stops[dir.index()][i] = stop;
+        }
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        x * self.cols + y
+    }
+
+    pub fn next(&self, pt: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let stop = self.stops[dir.index()][self.index(pt)];
+        (stop != EXIT).then(|| (stop as usize / self.cols, stop as usize % self.cols))
+    }
+
+    // One extra obstacle only changes the stops on its own row and column,
+    // the patch is applied on lookup so the table is never copied.
+    pub fn with_object(&self, object: (usize, usize)) -> Patched<'_> {
+        Patched {
+            table: self,
+            object,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Patched<'a> {
+    table: &'a JumpTable,
+    object: (usize, usize),
+}
+
+impl Patched<'_> {
+    pub fn next(&self, pt: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let stop = self.table.next(pt, dir);
+        let (x, y) = pt;
+        let (ox, oy) = self.object;
+        // the extra obstacle counts when it sits between the guard and the
+        // cell it would have stopped on, that cell included
+        let blocks = match dir {
+            Direction::Up => oy == y && ox < x && stop.is_none_or(|(sx, _)| ox >= sx),
+            Direction::Down => oy == y && ox > x && stop.is_none_or(|(sx, _)| ox <= sx),
+            Direction::Left => ox == x && oy < y && stop.is_none_or(|(_, sy)| oy >= sy),
+            Direction::Right => ox == x && oy > y && stop.is_none_or(|(_, sy)| oy <= sy),
+        };
+        if !blocks {
+            return stop;
+        }
+        Some(match dir {
+            Direction::Up => (ox + 1, oy),
+            Direction::Down => (ox - 1, oy),
+            Direction::Left => (ox, oy + 1),
+            Direction::Right => (ox, oy - 1),
+        })
+    }
+
+    // Only the cells the guard turns on are remembered, a loop always comes
+    // back to one of them facing the same way.
+    pub fn loops(&self, mut pt: (usize, usize), mut dir: Direction, seen: &mut Seen) -> bool {
+        seen.clear();
+        while let Some(stop) = self.next(pt, dir) {
+            pt = stop;
+            dir = dir.rotate();
+            if !seen.insert(pt, dir) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// (cell, heading) marks that are cleared by bumping a stamp instead of
+// touching the whole buffer, one trial per candidate obstacle adds up.
+#[derive(Debug, Clone)]
+pub struct Seen {
+    cols: usize,
+    stamp: u32,
+    marks: Vec<u32>,
+}
+
+impl Seen {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Seen {
+            cols,
+            stamp: 1,
+            marks: vec![0; rows * cols * 4],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.stamp += 1;
+    }
+
+    pub fn insert(&mut self, (x, y): (usize, usize), dir: Direction) -> bool {
+        let i = (x * self.cols + y) * 4 + dir.index();
+        let fresh = self.marks[i] != self.stamp;
+        self.marks[i] = self.stamp;
+        fresh
+    }
+}
+
+// Cells where one extra obstacle traps the guard in a loop, in the order the
+// guard first reaches them. Every trial starts right in front of its obstacle
+// since the route up to there is unchanged.
+pub fn loop_positions(puzzle: &Puzzle) -> Vec<(usize, usize)> {
+    let table = JumpTable::new(puzzle.rows, puzzle.cols, &puzzle.objects);
+    let mut tried = vec![false; puzzle.rows * puzzle.cols];
+    let mut seen = Seen::new(puzzle.rows, puzzle.cols);
+    let mut result = Vec::new();
+
+    tried[table.index(puzzle.guard.pt)] = true;
+    for step in puzzle.walk().path().windows(2) {
+        let (from, to) = (&step[0], &step[1]);
+        let i = table.index(to.pt);
+        // turns stay on the same cell and cells are only tried on first entry
+        if from.pt == to.pt || tried[i] {
+            continue;
+        }
+        tried[i] = true;
+        if table.with_object(to.pt).loops(from.pt, from.dir, &mut seen) {
+            result.push(to.pt);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn should_jump_to_next_obstacle() {
+        let puzzle = Puzzle::new(EXAMPLE);
+        let table = JumpTable::new(puzzle.rows, puzzle.cols, &puzzle.objects);
+        assert_eq!(table.next((6, 4), Direction::Up), Some((1, 4)));
+        assert_eq!(table.next((1, 4), Direction::Right), Some((1, 8)));
+        assert_eq!(table.next((6, 4), Direction::Down), None);
+        assert_eq!(table.next((6, 9), Direction::Left), Some((6, 2)));
+    }
+
+    #[test]
+    fn should_patch_one_obstacle() {
+        let puzzle = Puzzle::new(EXAMPLE);
+        let table = JumpTable::new(puzzle.rows, puzzle.cols, &puzzle.objects);
+        let patched = table.with_object((3, 4));
+        assert_eq!(patched.next((6, 4), Direction::Up), Some((4, 4)));
+        // behind the guard or past the real obstacle it changes nothing
+        assert_eq!(patched.next((2, 4), Direction::Up), Some((1, 4)));
+        assert_eq!(patched.next((6, 4), Direction::Down), None);
+        assert_eq!(patched.next((3, 0), Direction::Right), Some((3, 1)));
+        assert_eq!(patched.next((3, 3), Direction::Right), Some((3, 3)));
+    }
+
+    #[test]
+    fn should_list_loop_positions() {
+        let actual = loop_positions(&Puzzle::new(EXAMPLE));
+        let expected = vec![(6, 3), (7, 6), (8, 3), (8, 1), (7, 7), (9, 7)];
+        assert_eq!(expected, actual);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod jump;
+pub mod puzzle;
+pub mod render;
+pub mod sandbox;
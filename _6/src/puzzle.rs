@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::iproduct;
+use nom::{
+    character::complete::{line_ending, one_of},
+    multi::{many1, separated_list1},
+    IResult,
+};
+
+use crate::jump::loop_positions;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub guard: Guard,
+    pub rows: usize,
+    pub cols: usize,
+    pub objects: HashSet<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn rotate(&self) -> Direction {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn rotate_left(&self) -> Direction {
+        match self {
+            Self::Up => Self::Left,
+            Self::Left => Self::Down,
+            Self::Down => Self::Right,
+            Self::Right => Self::Up,
+        }
+    }
+
+    pub fn reverse(&self) -> Direction {
+        self.rotate().rotate()
+    }
+
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Self::Up),
+            '<' => Some(Self::Left),
+            '>' => Some(Self::Right),
+            'v' => Some(Self::Down),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Up => 0,
+            Self::Right => 1,
+            Self::Down => 2,
+            Self::Left => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Guard {
+    pub pt: (usize, usize),
+    pub dir: Direction,
+}
+
+impl Guard {
+    pub fn walk(&self) -> Option<(usize, usize)> {
+        let (x, y) = self.pt;
+        match &self.dir {
+            Direction::Up => x.checked_sub(1).map(|nx| (nx, y)),
+            Direction::Right => Some((x, y + 1)),
+            Direction::Down => Some((x + 1, y)),
+            Direction::Left => y.checked_sub(1).map(|ny| (x, ny)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    // the last state faces off the map
+    Exited {
+        path: Vec<Guard>,
+    },
+    // path[cycle_start..] repeats forever, the state after the last one is
+    // path[cycle_start] again
+    Looped {
+        cycle_start: usize,
+        cycle_len: usize,
+        path: Vec<Guard>,
+    },
+}
+
+impl Outcome {
+    pub fn path(&self) -> &[Guard] {
+        match self {
+            Outcome::Exited { path } | Outcome::Looped { path, .. } => path,
+        }
+    }
+
+    pub fn is_loop(&self) -> bool {
+        matches!(self, Outcome::Looped { .. })
+    }
+
+    // distinct cells the guard stood on, sorted
+    pub fn visited(&self) -> Vec<(usize, usize)> {
+        let mut cells: Vec<(usize, usize)> = self.path().iter().map(|g| g.pt).collect();
+        cells.sort();
+        cells.dedup();
+        cells
+    }
+}
+
+impl Puzzle {
+    pub fn new(input: &str) -> Self {
+        let (_, map) = parse_map(input).unwrap();
+        let mut guard: Option<Guard> = None;
+        let mut objects = HashSet::new();
+
+        for (x, y) in iproduct!(0..map.len(), 0..map[0].len()) {
+            let cell = map[x][y];
+            let pt = (x, y);
+
+            if cell == '#' {
+                objects.insert((x, y));
+            }
+
+            if let Some(dir) = Direction::from_char(cell) {
+                guard = Some(Guard { pt, dir });
+            }
+        }
+
+        let guard = guard.unwrap();
+
+        Self {
+            guard,
+            objects,
+            rows: map.len(),
+            cols: map[0].len(),
+        }
+    }
+
+    pub fn move_until_out(&self) -> usize {
+        self.walk().visited().len()
+    }
+
+    pub fn count_cycles(&self) -> usize {
+        loop_positions(self).len()
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.walk().is_loop()
+    }
+
+    // One state per step, a step either moves the guard one cell or turns it.
+    // The walk is deterministic, so seeing a state twice means it never ends.
+    pub fn walk(&self) -> Outcome {
+        let mut guard = self.guard.clone();
+        let mut path = Vec::new();
+        let mut seen = HashMap::new();
+
+        loop {
+            if let Some(&cycle_start) = seen.get(&guard) {
+                return Outcome::Looped {
+                    cycle_start,
+                    cycle_len: path.len() - cycle_start,
+                    path,
+                };
+            }
+            seen.insert(guard.clone(), path.len());
+            path.push(guard.clone());
+
+            let Some(next) = guard.walk().filter(|&pt| self.contains(pt)) else {
+                return Outcome::Exited { path };
+            };
+            if self.objects.contains(&next) {
+                guard.dir = guard.dir.rotate();
+            } else {
+                guard.pt = next;
+            }
+        }
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.rows && y < self.cols
+    }
+}
+
+fn parse_map(input: &str) -> IResult<&str, Vec<Vec<char>>> {
+    separated_list1(line_ending, many1(one_of(".#^><v")))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_exit_from_border() {
+        let actual = Puzzle::new("..^..\n.....").walk();
+        let expected = Outcome::Exited {
+            path: vec![Guard {
+                pt: (0, 2),
+                dir: Direction::Up,
+            }],
+        };
+        assert_eq!(expected, actual);
+        assert_eq!(Puzzle::new("..^..\n.....").move_until_out(), 1);
+    }
+
+    #[test]
+    fn should_walk_straight_without_obstacles() {
+        let actual = Puzzle::new(".....\n.....\n..>..").walk();
+        assert!(!actual.is_loop());
+        assert_eq!(actual.visited(), vec![(2, 2), (2, 3), (2, 4)]);
+    }
+
+    #[test]
+    fn should_loop_when_boxed_in() {
+        let actual = Puzzle::new(".#.\n#^#\n.#.").walk();
+        let Outcome::Looped {
+            cycle_start,
+            cycle_len,
+            path,
+        } = actual
+        else {
+            panic!("guard should be stuck");
+        };
+        assert_eq!((cycle_start, cycle_len), (0, 4));
+        assert!(path.iter().all(|g| g.pt == (1, 1)));
+    }
+
+    #[test]
+    fn should_find_cycle_after_lead_in() {
+        let given = ".#...
+....#
+.....
+#....
+.^.#.";
+        let Outcome::Looped {
+            cycle_start,
+            cycle_len,
+            path,
+        } = Puzzle::new(given).walk()
+        else {
+            panic!("guard should loop");
+        };
+        // the start cell is walked once, the rectangle begins one step later
+        assert_eq!(cycle_start, 1);
+        assert_eq!(cycle_len, 12);
+        assert_eq!(path.len(), cycle_start + cycle_len);
+        assert_eq!(path[cycle_start].pt, (3, 1));
+    }
+
+    #[test]
+    fn should_count_every_visit_once() {
+        // the guard crosses its own route and revisits a cell facing another way
+        let given = "..#....
+......#
+.......
+..^....
+.....#.";
+        let outcome = Puzzle::new(given).walk();
+        assert!(!outcome.is_loop());
+        let visited = outcome.visited();
+        let path = outcome.path();
+        assert!(path.len() > visited.len());
+        assert_eq!(Puzzle::new(given).move_until_out(), visited.len());
+    }
+}
@@ -0,0 +1,156 @@
+use std::io::{self, Write};
+
+use crate::puzzle::{Direction, Outcome, Puzzle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Floor,
+    Object,
+    Start(Direction),
+    Vertical,
+    Horizontal,
+    // walked both ways, or turned on
+    Cross,
+    // an obstacle placed here would trap the guard
+    Loop,
+}
+
+impl Cell {
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Floor => '.',
+            Cell::Object => '#',
+            Cell::Start(Direction::Up) => '^',
+            Cell::Start(Direction::Down) => 'v',
+            Cell::Start(Direction::Left) => '<',
+            Cell::Start(Direction::Right) => '>',
+            Cell::Vertical => '|',
+            Cell::Horizontal => '-',
+            Cell::Cross => '+',
+            Cell::Loop => 'O',
+        }
+    }
+
+    pub fn color(self) -> [u8; 3] {
+        match self {
+            Cell::Floor => [24, 24, 32],
+            Cell::Object => [160, 160, 160],
+            Cell::Start(_) => [64, 220, 64],
+            Cell::Vertical | Cell::Horizontal => [48, 96, 200],
+            Cell::Cross => [120, 170, 255],
+            Cell::Loop => [230, 48, 48],
+        }
+    }
+}
+
+// The map with the guard's route drawn the way the puzzle text does, and
+// every cell in `loops` marked over the route.
+pub fn cells(puzzle: &Puzzle, outcome: &Outcome, loops: &[(usize, usize)]) -> Vec<Vec<Cell>> {
+    let mut grid = vec![vec![Cell::Floor; puzzle.cols]; puzzle.rows];
+    for &(x, y) in &puzzle.objects {
+        grid[x][y] = Cell::Object;
+    }
+
+    for guard in outcome.path() {
+        let (x, y) = guard.pt;
+        let line = match guard.dir {
+            Direction::Up | Direction::Down => Cell::Vertical,
+            Direction::Left | Direction::Right => Cell::Horizontal,
+        };
+        grid[x][y] = match grid[x][y] {
+            Cell::Floor => line,
+            cell if cell == line => line,
+            _ => Cell::Cross,
+        };
+    }
+
+    for &(x, y) in loops {
+        grid[x][y] = Cell::Loop;
+    }
+    let (x, y) = puzzle.guard.pt;
+    grid[x][y] = Cell::Start(puzzle.guard.dir);
+    grid
+}
+
+pub fn render(grid: &[Vec<Cell>]) -> String {
+    grid.iter()
+        .map(|row| row.iter().map(|c| c.to_char()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Binary PPM, every cell a `scale` pixels wide square.
+pub fn write_ppm(mut w: impl Write, grid: &[Vec<Cell>], scale: usize) -> io::Result<()> {
+    let rows = grid.len();
+    let cols = grid.first().map_or(0, |row| row.len());
+    write!(w, "P6\n{} {}\n255\n", cols * scale, rows * scale)?;
+    for row in grid {
+        let line: Vec<u8> = row.iter().flat_map(|c| c.color().repeat(scale)).collect();
+        for _ in 0..scale {
+            w.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jump::loop_positions;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn should_draw_route() {
+        let puzzle = Puzzle::new(EXAMPLE);
+        let grid = cells(&puzzle, &puzzle.walk(), &[]);
+        let expected = "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+.+----++#.
+#+----+|..
+......#|..";
+        assert_eq!(expected, render(&grid));
+    }
+
+    #[test]
+    fn should_mark_loop_positions() {
+        let puzzle = Puzzle::new(EXAMPLE);
+        let grid = cells(&puzzle, &puzzle.walk(), &loop_positions(&puzzle));
+        let expected = "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+O^-+-+.
+.+----OO#.
+#O-O--+|..
+......#O..";
+        assert_eq!(expected, render(&grid));
+    }
+
+    #[test]
+    fn should_write_ppm() {
+        let grid = vec![vec![Cell::Floor, Cell::Loop]];
+        let mut actual = Vec::new();
+        write_ppm(&mut actual, &grid, 2).unwrap();
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&actual[..header.len()], header);
+        assert_eq!(actual.len(), header.len() + 4 * 2 * 3);
+        assert_eq!(&actual[header.len() + 6..header.len() + 9], &[230, 48, 48]);
+    }
+}
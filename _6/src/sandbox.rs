@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use crate::puzzle::{Direction, Guard};
+
+// What a guard does when the cell ahead is blocked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Turn {
+    #[default]
+    Right,
+    Left,
+    Reverse,
+}
+
+impl Turn {
+    pub fn apply(&self, dir: Direction) -> Direction {
+        match self {
+            Turn::Right => dir.rotate(),
+            Turn::Left => dir.rotate_left(),
+            Turn::Reverse => dir.reverse(),
+        }
+    }
+}
+
+// What happens when a guard steps past the edge of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Edges {
+    #[default]
+    Exit,
+    // it comes back in on the opposite side
+    Wrap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub turn: Turn,
+    pub edges: Edges,
+    pub obstacles: Vec<char>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            turn: Turn::default(),
+            edges: Edges::default(),
+            obstacles: vec!['#'],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub start: Guard,
+    // sorted
+    pub visited: Vec<(usize, usize)>,
+    pub loops: bool,
+}
+
+// A map with any number of guards. They step one after the other in the
+// order they appear on the map, and a guard still on the map blocks the
+// others the same way an obstacle does.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    rows: usize,
+    cols: usize,
+    objects: HashSet<(usize, usize)>,
+    guards: Vec<Guard>,
+    rules: Rules,
+}
+
+impl Sandbox {
+    // Anything that is neither a guard nor one of the rule's obstacles is
+    // floor, short lines are padded with floor.
+    pub fn new(input: &str, rules: Rules) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let mut objects = HashSet::new();
+        let mut guards = Vec::new();
+
+        for (x, line) in lines.iter().enumerate() {
+            for (y, cell) in line.chars().enumerate() {
+                if rules.obstacles.contains(&cell) {
+                    objects.insert((x, y));
+                } else if let Some(dir) = Direction::from_char(cell) {
+                    guards.push(Guard { pt: (x, y), dir });
+                }
+            }
+        }
+
+        Sandbox {
+            rows: lines.len(),
+            cols: lines.iter().map(|l| l.chars().count()).max().unwrap_or(0),
+            objects,
+            guards,
+            rules,
+        }
+    }
+
+    fn ahead(&self, guard: &Guard) -> Option<(usize, usize)> {
+        let (x, y) = guard.pt;
+        match self.rules.edges {
+            Edges::Exit => guard
+                .walk()
+                .filter(|&(x, y)| x < self.rows && y < self.cols),
+            Edges::Wrap => Some(match guard.dir {
+                Direction::Up => ((x + self.rows - 1) % self.rows, y),
+                Direction::Down => ((x + 1) % self.rows, y),
+                Direction::Left => (x, (y + self.cols - 1) % self.cols),
+                Direction::Right => (x, (y + 1) % self.cols),
+            }),
+        }
+    }
+
+    // None once the guard has left the map
+    fn step(&self, i: usize, guards: &[Option<Guard>]) -> Option<Guard> {
+        let guard = guards[i].as_ref()?;
+        let next = self.ahead(guard)?;
+        let blocked = self.objects.contains(&next)
+            || guards
+                .iter()
+                .enumerate()
+                .any(|(j, other)| j != i && other.as_ref().is_some_and(|g| g.pt == next));
+        if blocked {
+            Some(Guard {
+                pt: guard.pt,
+                dir: self.rules.turn.apply(guard.dir),
+            })
+        } else {
+            Some(Guard {
+                pt: next,
+                dir: guard.dir,
+            })
+        }
+    }
+
+    // Guards depend on each other, so it's the state of all of them together
+    // that has to repeat: whoever is still on the map then loops forever.
+    pub fn run(&self) -> Vec<Report> {
+        let mut guards: Vec<Option<Guard>> = self.guards.iter().cloned().map(Some).collect();
+        let mut visited: Vec<HashSet<(usize, usize)>> =
+            self.guards.iter().map(|g| HashSet::from([g.pt])).collect();
+        let mut seen = HashSet::new();
+
+        while guards.iter().any(Option::is_some) && seen.insert(guards.clone()) {
+            for i in 0..guards.len() {
+                guards[i] = self.step(i, &guards);
+                if let Some(guard) = &guards[i] {
+                    visited[i].insert(guard.pt);
+                }
+            }
+        }
+
+        self.guards
+            .iter()
+            .zip(visited)
+            .zip(guards)
+            .map(|((start, visited), end)| {
+                let mut visited: Vec<(usize, usize)> = visited.into_iter().collect();
+                visited.sort();
+                Report {
+                    start: start.clone(),
+                    visited,
+                    loops: end.is_some(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn should_match_puzzle_rules() {
+        let actual = Sandbox::new(EXAMPLE, Rules::default()).run();
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].visited.len(), 41);
+        assert!(!actual[0].loops);
+    }
+
+    #[test]
+    fn should_turn_as_configured() {
+        let given = "#...\n^...";
+        let right = Sandbox::new(given, Rules::default()).run();
+        assert_eq!(right[0].visited, vec![(1, 0), (1, 1), (1, 2), (1, 3)]);
+
+        let rules = Rules {
+            turn: Turn::Left,
+            ..Rules::default()
+        };
+        assert_eq!(Sandbox::new(given, rules).run()[0].visited, vec![(1, 0)]);
+
+        let rules = Rules {
+            turn: Turn::Reverse,
+            ..Rules::default()
+        };
+        let actual = Sandbox::new(EXAMPLE, rules).run();
+        assert_eq!(
+            actual[0].visited,
+            (1..10).map(|x| (x, 4)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_loop_around_wrapped_edges() {
+        let rules = Rules {
+            edges: Edges::Wrap,
+            ..Rules::default()
+        };
+        let actual = Sandbox::new(".>..\n....", rules).run();
+        assert!(actual[0].loops);
+        assert_eq!(actual[0].visited, vec![(0, 0), (0, 1), (0, 2), (0, 3)]);
+    }
+
+    #[test]
+    fn should_block_other_guards() {
+        let actual = Sandbox::new("..v..\n.....\n..^..", Rules::default()).run();
+        let expected = vec![
+            Report {
+                start: Guard {
+                    pt: (0, 2),
+                    dir: Direction::Down,
+                },
+                visited: vec![(0, 2), (1, 0), (1, 1), (1, 2)],
+                loops: false,
+            },
+            Report {
+                start: Guard {
+                    pt: (2, 2),
+                    dir: Direction::Up,
+                },
+                visited: vec![(2, 2), (2, 3), (2, 4)],
+                loops: false,
+            },
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_use_custom_obstacles() {
+        let rules = Rules {
+            obstacles: vec!['O', '%'],
+            ..Rules::default()
+        };
+        let actual = Sandbox::new("#..\n^..\n%..", rules.clone()).run();
+        // `#` is floor now
+        assert_eq!(actual[0].visited, vec![(0, 0), (1, 0)]);
+        let actual = Sandbox::new("O..\n^..\n%..", rules).run();
+        assert_eq!(actual[0].visited, vec![(1, 0), (1, 1), (1, 2)]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use crate::operator::{Inverse, Operator};
+
+// Works from the target back to the first number, undoing the last operator
+// against the last operand. Subtraction can't go below zero, division has to
+// be exact and a suffix has to match, so most branches die right away instead
+// of growing a set of values the way the forward search does. None when an
+// operator can't be undone, `eval::brute_force` takes any operator.
+pub fn solvable(target: u128, nums: &[u128], ops: &[&dyn Operator]) -> Option<bool> {
+    if !ops.iter().all(|op| op.invertible()) {
+        return None;
+    }
+    let mut dead = HashSet::new();
+    let mut stuck = HashSet::new();
+    Some(reachable(
+        nums.len() - 1,
+        target,
+        nums,
+        ops,
+        &mut dead,
+        &mut stuck,
+    ))
+}
+
+// can nums[..=i] be made to evaluate to `value`
+fn reachable(
+    i: usize,
+    value: u128,
+    nums: &[u128],
+    ops: &[&dyn Operator],
+    dead: &mut HashSet<(usize, u128)>,
+    stuck: &mut HashSet<(usize, usize, u128)>,
+) -> bool {
+    if i == 0 {
+        return value == nums[0];
+    }
+    if dead.contains(&(i, value)) {
+        return false;
+    }
+    let b = nums[i];
+    let found = ops.iter().any(|op| match op.inverse(value, b) {
+        Inverse::One(a) => reachable(i - 1, a, nums, ops, dead, stuck),
+        // any value will do, as long as nums[..i] evaluates to one at all
+        Inverse::Any => defined(0, nums[0], i - 1, nums, ops, stuck),
+        Inverse::None => false,
+    });
+    if !found {
+        dead.insert((i, value));
+    }
+    found
+}
+
+// can `value` at nums[j] be carried on to nums[end] without any operator
+// failing, dead ends are remembered in `stuck`
+fn defined(
+    j: usize,
+    value: u128,
+    end: usize,
+    nums: &[u128],
+    ops: &[&dyn Operator],
+    stuck: &mut HashSet<(usize, usize, u128)>,
+) -> bool {
+    if j == end {
+        return true;
+    }
+    if stuck.contains(&(j, end, value)) {
+        return false;
+    }
+    let found = ops.iter().any(|op| {
+        op.apply(value, nums[j + 1])
+            .is_some_and(|v| defined(j + 1, v, end, nums, ops, stuck))
+    });
+    if !found {
+        stuck.insert((j, end, value));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval::{evaluate, Order},
+        operator::{Add, Div, Pow, Sub, Xor, PLUS_TIMES, WITH_CONCAT},
+    };
+
+    #[test]
+    fn should_solve_examples() {
+        assert_eq!(solvable(3267, &[81, 40, 27], &PLUS_TIMES), Some(true));
+        assert_eq!(solvable(21037, &[9, 7, 18, 13], &WITH_CONCAT), Some(false));
+        assert_eq!(solvable(7290, &[6, 8, 6, 15], &WITH_CONCAT), Some(true));
+        assert_eq!(solvable(7290, &[6, 8, 6, 15], &PLUS_TIMES), Some(false));
+        assert_eq!(solvable(0, &[5, 0], &PLUS_TIMES), Some(true));
+    }
+
+    #[test]
+    fn should_solve_long_equations() {
+        let nums: Vec<u128> = (0..40).map(|i| (i * 7 % 13) + 1).collect();
+        let ops: Vec<&dyn Operator> = (0..39).map(|i| WITH_CONCAT[i * 5 % 7 % 3]).collect();
+        let target = evaluate(&nums, &ops, Order::LeftToRight).unwrap();
+        assert_eq!(solvable(target, &nums, &WITH_CONCAT), Some(true));
+        assert_eq!(solvable(target, &nums, &PLUS_TIMES), Some(false));
+    }
+
+    #[test]
+    fn should_undo_other_operators() {
+        let ops: [&dyn Operator; 2] = [&Sub, &Xor];
+        // 9 - 4 ^ 3 == 6
+        assert_eq!(solvable(6, &[9, 4, 3], &ops), Some(true));
+        assert_eq!(solvable(6, &[9, 4, 3], &[&Add, &Div]), None);
+    }
+
+    #[test]
+    fn should_need_a_defined_prefix() {
+        let ops: [&dyn Operator; 2] = [&Pow, &Sub];
+        // x ** 0 is 1 for any x, but 3 - 200 and 3 ** 200 have no value
+        assert_eq!(solvable(1, &[3, 200, 0], &ops), Some(false));
+        assert_eq!(solvable(1, &[3, 2, 0], &ops), Some(true));
+    }
+}
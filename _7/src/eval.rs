@@ -0,0 +1,148 @@
+use crate::operator::Operator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    // what the puzzle asks for
+    #[default]
+    LeftToRight,
+    // operators with a higher `precedence` go first
+    Precedence,
+}
+
+// None when some step overflows or isn't defined
+pub fn evaluate(nums: &[u128], ops: &[&dyn Operator], order: Order) -> Option<u128> {
+    match order {
+        Order::LeftToRight => ops
+            .iter()
+            .zip(&nums[1..])
+            .try_fold(nums[0], |a, (op, &b)| op.apply(a, b)),
+        Order::Precedence => with_precedence(nums, ops),
+    }
+}
+
+// Shunting-yard: an operator waits on the stack until one that binds less
+// tightly shows up.
+fn with_precedence(nums: &[u128], ops: &[&dyn Operator]) -> Option<u128> {
+    let mut values = vec![nums[0]];
+    let mut pending: Vec<&dyn Operator> = Vec::new();
+
+    fn reduce(values: &mut Vec<u128>, op: &dyn Operator) -> Option<()> {
+        let b = values.pop()?;
+        let a = values.pop()?;
+        values.push(op.apply(a, b)?);
+        Some(())
+    }
+
+    for (&op, &n) in ops.iter().zip(&nums[1..]) {
+        while let Some(&top) = pending.last() {
+            let first = top.precedence() > op.precedence()
+                || (top.precedence() == op.precedence() && !op.right_assoc());
+            if !first {
+                break;
+            }
+            pending.pop();
+            reduce(&mut values, top)?;
+        }
+        pending.push(op);
+        values.push(n);
+    }
+    while let Some(op) = pending.pop() {
+        reduce(&mut values, op)?;
+    }
+    values.pop()
+}
+
+// most assignments `brute_force` tries, 3 operators fill 15 slots
+pub const MAX_ASSIGNMENTS: usize = 1 << 24;
+
+// Every assignment of `ops` that evaluates to `target`. It tries all of
+// them, which is only fine for short equations, but works for any operators
+// and either order. None when there are more than MAX_ASSIGNMENTS.
+pub fn brute_force<'a>(
+    target: u128,
+    nums: &[u128],
+    ops: &[&'a dyn Operator],
+    order: Order,
+) -> Option<Vec<Vec<&'a dyn Operator>>> {
+    let slots = nums.len() - 1;
+    let total = u32::try_from(slots)
+        .ok()
+        .and_then(|slots| ops.len().checked_pow(slots))
+        .filter(|&total| total <= MAX_ASSIGNMENTS)?;
+    let result = (0..total)
+        .map(|mut code| {
+            (0..slots)
+                .map(|_| {
+                    let op = ops[code % ops.len()];
+                    code /= ops.len();
+                    op
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|assignment| evaluate(nums, assignment, order) == Some(target))
+        .collect();
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{Add, Concat, Mul, Pow, Sub, Xor};
+
+    #[test]
+    fn should_evaluate_left_to_right() {
+        assert_eq!(
+            evaluate(&[81, 40, 27], &[&Add, &Mul], Order::LeftToRight),
+            Some(3267)
+        );
+        assert_eq!(evaluate(&[1, 2], &[&Sub], Order::LeftToRight), None);
+        assert_eq!(evaluate(&[7], &[], Order::LeftToRight), Some(7));
+    }
+
+    #[test]
+    fn should_honour_precedence() {
+        assert_eq!(
+            evaluate(&[81, 40, 27], &[&Add, &Mul], Order::Precedence),
+            Some(1161)
+        );
+        assert_eq!(
+            evaluate(&[10, 4, 3], &[&Sub, &Sub], Order::Precedence),
+            Some(3)
+        );
+        // right associative: 2 ** (3 ** 2)
+        assert_eq!(
+            evaluate(&[2, 3, 2], &[&Pow, &Pow], Order::Precedence),
+            Some(512)
+        );
+        assert_eq!(
+            evaluate(&[2, 3, 4], &[&Mul, &Concat], Order::Precedence),
+            Some(68)
+        );
+        assert_eq!(
+            evaluate(&[6, 1, 2], &[&Xor, &Add], Order::Precedence),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn should_search_any_order() {
+        let ops: [&dyn Operator; 2] = [&Add, &Mul];
+        assert_eq!(
+            brute_force(3267, &[81, 40, 27], &ops, Order::LeftToRight).map(|s| s.len()),
+            Some(2)
+        );
+        assert_eq!(
+            brute_force(3267, &[81, 40, 27], &ops, Order::Precedence).map(|s| s.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn should_give_up_on_long_equations() {
+        let ops: [&dyn Operator; 3] = [&Add, &Mul, &Concat];
+        assert!(brute_force(10, &[1; 10], &ops[..2], Order::Precedence).is_some());
+        assert!(brute_force(16, &[1; 17], &ops, Order::Precedence).is_none());
+        // 3^44 overflows usize
+        assert!(brute_force(45, &[1; 45], &ops, Order::Precedence).is_none());
+    }
+}
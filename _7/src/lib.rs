@@ -0,0 +1,4 @@
+pub mod backward;
+pub mod eval;
+pub mod operator;
+pub mod solve;
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{env, error::Error};
 
 use nom::{
     character::complete::{char, line_ending, space0, space1, u128},
@@ -8,20 +8,81 @@ use nom::{
     IResult,
 };
 
+use _7::{
+    backward::solvable,
+    eval::{brute_force, Order},
+    operator::{by_symbol, Operator, PLUS_TIMES, WITH_CONCAT},
+    solve::{expression, Solver},
+};
+
 #[derive(Debug, PartialEq, Eq)]
 struct Formula {
     target: u128,
     nums: Vec<u128>,
 }
 
-type BinOp = fn(u128, u128) -> u128;
-
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     let (_, formulas) = parse_file(include_str!("../puzzle.txt")).unwrap();
+
+    // --show prints one equation for every calibration that can be solved,
+    // with the operators from --ops (like `+,*,||`) and, given --precedence,
+    // standard precedence instead of left to right. With --precedence every
+    // assignment is tried, equations with more than eval::MAX_ASSIGNMENTS of
+    // them are skipped.
+    let mut show_equations = false;
+    let mut ops: Vec<&dyn Operator> = WITH_CONCAT.to_vec();
+    let mut order = Order::LeftToRight;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--show" => show_equations = true,
+            "--precedence" => order = Order::Precedence,
+            "--ops" => {
+                let symbols = args.next().ok_or("--ops needs a list of operators")?;
+                ops = symbols
+                    .split(',')
+                    .map(|s| by_symbol(s).ok_or(format!("unknown operator {}", s)))
+                    .collect::<Result<_, _>>()?;
+            }
+            _ => return Err(format!("unknown argument {}", arg).into()),
+        }
+    }
+    if show_equations {
+        show(&formulas, &ops, order);
+    }
+
     let total_calibration = total_calibration(&formulas);
     let total_calibration_concat = total_calibration_concat(&formulas);
     println!("result 1: {}", total_calibration);
     println!("result 2: {}", total_calibration_concat);
+
+    Ok(())
+}
+
+fn show(formulas: &[Formula], ops: &[&dyn Operator], order: Order) {
+    for Formula { target, nums } in formulas {
+        let (first, count) = match order {
+            Order::LeftToRight => {
+                let mut solver = Solver::new(*target, nums, ops);
+                (solver.first(), solver.count())
+            }
+            Order::Precedence => {
+                let Some(solutions) = brute_force(*target, nums, ops, order) else {
+                    println!("{}: skipped, too many operators to try", target);
+                    continue;
+                };
+                let count = solutions.len() as u128;
+                (solutions.into_iter().next(), count)
+            }
+        };
+        let Some(first) = first else { continue };
+        println!(
+            "{} = {} ({} solutions)",
+            target,
+            expression(nums, &first),
+            count
+        );
+    }
 }
 
 fn parse_file(input: &str) -> IResult<&str, Vec<Formula>> {
@@ -39,32 +100,12 @@ fn parse_formula(input: &str) -> IResult<&str, Formula> {
     )(input)
 }
 
-fn dyn_prog(target: u128, nums: &[u128], ops: &[BinOp]) -> bool {
-    let mut dp = vec![HashSet::new(); nums.len()];
-
-    dp[0].insert(nums[0]);
-
-    for i in 1..nums.len() {
-        for val in dp[i - 1].clone() {
-            for op in ops {
-                dp[i].insert(op(val, nums[i]));
-            }
-        }
-    }
-
-    dp[nums.len() - 1].contains(&target)
-}
-
 fn has_target(target: u128, nums: &[u128]) -> bool {
-    dyn_prog(target, nums, &vec![|a, b| a * b, |a, b| a + b])
+    solvable(target, nums, &PLUS_TIMES) == Some(true)
 }
 
 fn has_target_concat(target: u128, nums: &[u128]) -> bool {
-    dyn_prog(target, nums, &vec![|a, b| a * b, |a, b| a + b, conc])
-}
-
-fn conc(a: u128, b: u128) -> u128 {
-    format!("{}{}", a, b).parse::<u128>().unwrap()
+    solvable(target, nums, &WITH_CONCAT) == Some(true)
 }
 
 fn total_calibration(arr: &[Formula]) -> u128 {
@@ -83,8 +124,62 @@ fn total_calibration_concat(arr: &[Formula]) -> u128 {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
 
+    type BinOp = fn(u128, u128) -> u128;
+
+    // the forward search the backward one replaced, kept to check it against
+    fn dyn_prog(target: u128, nums: &[u128], ops: &[BinOp]) -> bool {
+        let mut dp = vec![HashSet::new(); nums.len()];
+
+        dp[0].insert(nums[0]);
+
+        for i in 1..nums.len() {
+            for val in dp[i - 1].clone() {
+                for op in ops {
+                    dp[i].insert(op(val, nums[i]));
+                }
+            }
+        }
+
+        dp[nums.len() - 1].contains(&target)
+    }
+
+    fn conc(a: u128, b: u128) -> u128 {
+        format!("{}{}", a, b).parse::<u128>().unwrap()
+    }
+
+    #[test]
+    fn should_agree_with_forward_search() {
+        let mut seed: u64 = 7;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        for _ in 0..300 {
+            let nums: Vec<u128> = (0..2 + next(6)).map(|_| next(20) as u128).collect();
+            let target = next(5000) as u128;
+            assert_eq!(
+                has_target(target, &nums),
+                dyn_prog(target, &nums, &[|a, b| a * b, |a, b| a + b]),
+                "{}: {:?}",
+                target,
+                nums
+            );
+            assert_eq!(
+                has_target_concat(target, &nums),
+                dyn_prog(target, &nums, &[|a, b| a * b, |a, b| a + b, conc]),
+                "{}: {:?}",
+                target,
+                nums
+            );
+        }
+    }
+
     #[test]
     fn should_find_target() {
         let target = 3267;
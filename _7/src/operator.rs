@@ -0,0 +1,331 @@
+use std::fmt;
+
+// What `inverse` found for apply(a, b) == result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inverse {
+    None,
+    One(u128),
+    // every `a` works, like `a * 0 == 0`
+    Any,
+}
+
+pub trait Operator: fmt::Debug {
+    fn name(&self) -> &str;
+    fn symbol(&self) -> &str;
+
+    // None when the result doesn't fit or isn't defined
+    fn apply(&self, a: u128, b: u128) -> Option<u128>;
+
+    // Operators that can't undo themselves leave these alone, the backward
+    // search refuses them then.
+    fn invertible(&self) -> bool {
+        false
+    }
+
+    fn inverse(&self, _result: u128, _b: u128) -> Inverse {
+        Inverse::None
+    }
+
+    // higher binds tighter when evaluating with precedence
+    fn precedence(&self) -> u8;
+
+    fn right_assoc(&self) -> bool {
+        false
+    }
+}
+
+fn exact(a: Option<u128>) -> Inverse {
+    a.map_or(Inverse::None, Inverse::One)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Add;
+
+impl Operator for Add {
+    fn name(&self) -> &str {
+        "add"
+    }
+
+    fn symbol(&self) -> &str {
+        "+"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_add(b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+
+    fn inverse(&self, result: u128, b: u128) -> Inverse {
+        exact(result.checked_sub(b))
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Mul;
+
+impl Operator for Mul {
+    fn name(&self) -> &str {
+        "multiply"
+    }
+
+    fn symbol(&self) -> &str {
+        "*"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_mul(b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+
+    fn inverse(&self, result: u128, b: u128) -> Inverse {
+        match (result, b) {
+            (0, 0) => Inverse::Any,
+            (_, 0) => Inverse::None,
+            _ => exact(result.is_multiple_of(b).then(|| result / b)),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+}
+
+// `12 || 345 == 12345`
+#[derive(Debug, Clone, Copy)]
+pub struct Concat;
+
+pub fn digits(mut n: u128) -> u32 {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+impl Operator for Concat {
+    fn name(&self) -> &str {
+        "concatenate"
+    }
+
+    fn symbol(&self) -> &str {
+        "||"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_mul(10u128.checked_pow(digits(b))?)?
+            .checked_add(b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+
+    fn inverse(&self, result: u128, b: u128) -> Inverse {
+        let Some(shift) = 10u128.checked_pow(digits(b)) else {
+            return Inverse::None;
+        };
+        exact((result % shift == b).then(|| result / shift))
+    }
+
+    fn precedence(&self) -> u8 {
+        5
+    }
+}
+
+// never goes below zero
+#[derive(Debug, Clone, Copy)]
+pub struct Sub;
+
+impl Operator for Sub {
+    fn name(&self) -> &str {
+        "subtract"
+    }
+
+    fn symbol(&self) -> &str {
+        "-"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_sub(b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+
+    fn inverse(&self, result: u128, b: u128) -> Inverse {
+        exact(result.checked_add(b))
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+}
+
+// Rounds down, so a result comes from b different `a` and there is no
+// inverse.
+#[derive(Debug, Clone, Copy)]
+pub struct Div;
+
+impl Operator for Div {
+    fn name(&self) -> &str {
+        "divide"
+    }
+
+    fn symbol(&self) -> &str {
+        "/"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_div(b)
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pow;
+
+impl Operator for Pow {
+    fn name(&self) -> &str {
+        "power"
+    }
+
+    fn symbol(&self) -> &str {
+        "**"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_pow(b.try_into().ok()?)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+
+    // the integer b-th root of result, when it is exact
+    fn inverse(&self, result: u128, b: u128) -> Inverse {
+        match (result, b) {
+            (1, 0) => return Inverse::Any,
+            (_, 0) => return Inverse::None,
+            (0 | 1, _) | (_, 1) => return Inverse::One(result),
+            _ => {}
+        }
+        // 2^128 doesn't fit, so the root is below 2^(128 / b), rounded up
+        // when b doesn't divide 128
+        let (mut lo, mut hi) = (1u128, 1u128 << (128 / b.min(128) + 1));
+        while lo < hi {
+            let mid = lo + (hi - lo).div_ceil(2);
+            match self.apply(mid, b) {
+                Some(v) if v <= result => lo = mid,
+                _ => hi = mid - 1,
+            }
+        }
+        exact((self.apply(lo, b) == Some(result)).then_some(lo))
+    }
+
+    fn precedence(&self) -> u8 {
+        4
+    }
+
+    fn right_assoc(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Xor;
+
+impl Operator for Xor {
+    fn name(&self) -> &str {
+        "xor"
+    }
+
+    fn symbol(&self) -> &str {
+        "^"
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        Some(a ^ b)
+    }
+
+    fn invertible(&self) -> bool {
+        true
+    }
+
+    fn inverse(&self, result: u128, b: u128) -> Inverse {
+        Inverse::One(result ^ b)
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+}
+
+pub const PLUS_TIMES: [&dyn Operator; 2] = [&Add, &Mul];
+pub const WITH_CONCAT: [&dyn Operator; 3] = [&Add, &Mul, &Concat];
+pub const BUILTIN: [&dyn Operator; 7] = [&Add, &Mul, &Concat, &Sub, &Div, &Pow, &Xor];
+
+pub fn by_symbol(symbol: &str) -> Option<&'static dyn Operator> {
+    BUILTIN.into_iter().find(|op| op.symbol() == symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_apply_checked() {
+        assert_eq!(Concat.apply(12, 345), Some(12345));
+        assert_eq!(Concat.apply(7, 0), Some(70));
+        assert_eq!(Concat.apply(u128::MAX / 10, 99), None);
+        assert_eq!(Sub.apply(3, 5), None);
+        assert_eq!(Div.apply(7, 2), Some(3));
+        assert_eq!(Div.apply(7, 0), None);
+        assert_eq!(Pow.apply(3, 4), Some(81));
+        assert_eq!(Pow.apply(2, 128), None);
+        assert_eq!(Xor.apply(0b1100, 0b1010), Some(0b0110));
+    }
+
+    #[test]
+    fn should_invert() {
+        for op in BUILTIN.into_iter().filter(|op| op.invertible()) {
+            for (a, b) in [(0, 1), (5, 3), (81, 4), (12, 2), (3, 9)] {
+                let Some(result) = op.apply(a, b) else {
+                    continue;
+                };
+                assert_eq!(op.inverse(result, b), Inverse::One(a), "{:?}", op);
+            }
+        }
+        assert_eq!(Mul.inverse(0, 0), Inverse::Any);
+        assert_eq!(Pow.inverse(1, 0), Inverse::Any);
+        assert_eq!(Pow.inverse(82, 4), Inverse::None);
+        // roots close to the top of the range when b doesn't divide 128
+        for (a, b) in [((1 << 42) + 1, 3), (3_486_784_401, 3), (81, 20)] {
+            let result = Pow.apply(a, b).unwrap();
+            assert_eq!(Pow.inverse(result, b), Inverse::One(a), "{}^{}", a, b);
+        }
+        assert_eq!(Concat.inverse(156, 56), Inverse::One(1));
+        assert_eq!(Concat.inverse(156, 7), Inverse::None);
+        assert!(!Div.invertible());
+    }
+
+    #[test]
+    fn should_find_by_symbol() {
+        assert_eq!(by_symbol("||").map(|op| op.name()), Some("concatenate"));
+        assert!(by_symbol("%").is_none());
+    }
+}
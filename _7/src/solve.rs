@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use crate::operator::Operator;
+
+// `81 * 40 + 27`, operators are evaluated left to right
+pub fn expression(nums: &[u128], ops: &[&dyn Operator]) -> String {
+    let mut result = nums[0].to_string();
+    for (op, n) in ops.iter().zip(&nums[1..]) {
+        result.push_str(&format!(" {} {}", op.symbol(), n));
+    }
+    result
+}
+
+// Operator assignments for `nums` that evaluate to `target`. The number of
+// ways to finish from a partial value is memoised, so counting never has to
+// enumerate, and listing never goes down a branch without a solution.
+// Counts saturate at u128::MAX rather than overflow.
+pub struct Solver<'a> {
+    target: u128,
+    nums: &'a [u128],
+    ops: &'a [&'a dyn Operator],
+    ways: HashMap<(usize, u128), u128>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(target: u128, nums: &'a [u128], ops: &'a [&'a dyn Operator]) -> Self {
+        Solver {
+            target,
+            nums,
+            ops,
+            ways: HashMap::new(),
+        }
+    }
+
+    // assignments for nums[i + 1..] that take `value` to the target
+    fn ways(&mut self, i: usize, value: u128) -> u128 {
+        if i + 1 == self.nums.len() {
+            return (value == self.target) as u128;
+        }
+        if let Some(&n) = self.ways.get(&(i, value)) {
+            return n;
+        }
+        let next = self.nums[i + 1];
+        let n = self
+            .ops
+            .iter()
+            .map(|op| op.apply(value, next).map_or(0, |v| self.ways(i + 1, v)))
+            .fold(0, u128::saturating_add);
+        self.ways.insert((i, value), n);
+        n
+    }
+
+    pub fn count(&mut self) -> u128 {
+        self.ways(0, self.nums[0])
+    }
+
+    pub fn first(&mut self) -> Option<Vec<&'a dyn Operator>> {
+        let mut value = self.nums[0];
+        let mut result = Vec::new();
+        for i in 1..self.nums.len() {
+            let (op, next) = self.ops.iter().find_map(|&op| {
+                let next = op.apply(value, self.nums[i])?;
+                (self.ways(i, next) > 0).then_some((op, next))
+            })?;
+            value = next;
+            result.push(op);
+        }
+        (value == self.target).then_some(result)
+    }
+
+    pub fn all(&mut self) -> Vec<Vec<&'a dyn Operator>> {
+        let mut result = Vec::new();
+        let mut current = Vec::new();
+        self.collect(0, self.nums[0], &mut current, &mut result);
+        result
+    }
+
+    fn collect(
+        &mut self,
+        i: usize,
+        value: u128,
+        current: &mut Vec<&'a dyn Operator>,
+        result: &mut Vec<Vec<&'a dyn Operator>>,
+    ) {
+        if self.ways(i, value) == 0 {
+            return;
+        }
+        if i + 1 == self.nums.len() {
+            result.push(current.clone());
+            return;
+        }
+        for &op in self.ops {
+            let Some(next) = op.apply(value, self.nums[i + 1]) else {
+                continue;
+            };
+            current.push(op);
+            self.collect(i + 1, next, current, result);
+            current.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{Div, Mul, Pow, PLUS_TIMES, WITH_CONCAT};
+
+    #[test]
+    fn should_render_first_solution() {
+        let nums = [81, 40, 27];
+        let ops = Solver::new(3267, &nums, &PLUS_TIMES).first().unwrap();
+        assert_eq!(expression(&nums, &ops), "81 + 40 * 27");
+    }
+
+    #[test]
+    fn should_list_every_solution() {
+        let nums = [81, 40, 27];
+        let actual: Vec<String> = Solver::new(3267, &nums, &PLUS_TIMES)
+            .all()
+            .iter()
+            .map(|ops| expression(&nums, ops))
+            .collect();
+        assert_eq!(actual, vec!["81 + 40 * 27", "81 * 40 + 27"]);
+    }
+
+    #[test]
+    fn should_count_solutions() {
+        assert_eq!(Solver::new(3267, &[81, 40, 27], &PLUS_TIMES).count(), 2);
+        assert_eq!(Solver::new(292, &[11, 6, 16, 20], &PLUS_TIMES).count(), 1);
+        assert_eq!(Solver::new(21037, &[9, 7, 18, 13], &WITH_CONCAT).count(), 0);
+        // exactly one of the three operators has to be `*`
+        assert_eq!(Solver::new(3, &[1, 1, 1, 1], &PLUS_TIMES).count(), 3);
+    }
+
+    #[test]
+    fn should_count_past_u64() {
+        // every operator keeps 1 at 1, so any assignment works
+        let ops: [&dyn Operator; 3] = [&Mul, &Pow, &Div];
+        assert_eq!(Solver::new(1, &[1; 45], &ops).count(), 3u128.pow(44));
+        assert_eq!(Solver::new(1, &[1; 82], &ops).count(), u128::MAX);
+    }
+
+    #[test]
+    fn should_solve_with_concat() {
+        let nums = [6, 8, 6, 15];
+        let mut solver = Solver::new(7290, &nums, &WITH_CONCAT);
+        let ops = solver.first().unwrap();
+        assert_eq!(expression(&nums, &ops), "6 * 8 || 6 * 15");
+        assert!(Solver::new(7290, &nums, &PLUS_TIMES).first().is_none());
+    }
+}
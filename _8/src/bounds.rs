@@ -0,0 +1,46 @@
+// The rectangle of cells a map covers, coordinates are (row, column).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Bounds {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Bounds { rows, cols }
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.rows && (y as usize) < self.cols
+    }
+
+    // row by row
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32)> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |x| (0..cols).map(move |y| (x as i32, y as i32)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_check_both_axes() {
+        let bounds = Bounds::new(3, 10);
+        assert!(bounds.contains((0, 0)));
+        assert!(bounds.contains((2, 9)));
+        assert!(!bounds.contains((3, 0)));
+        assert!(!bounds.contains((0, 10)));
+        assert!(!bounds.contains((9, 2)));
+        assert!(!bounds.contains((-1, 0)));
+        assert!(!bounds.contains((0, -1)));
+    }
+
+    #[test]
+    fn should_list_cells_row_by_row() {
+        let actual: Vec<(i32, i32)> = Bounds::new(2, 3).cells().collect();
+        assert_eq!(actual, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(Bounds::new(0, 5).cells().count(), 0);
+    }
+}
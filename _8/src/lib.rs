@@ -0,0 +1,5 @@
+pub mod bounds;
+pub mod puzzle;
+pub mod report;
+pub mod rule;
+pub mod space;
@@ -1,136 +1,69 @@
-use itertools::Itertools;
-use std::collections::HashMap;
-
-#[derive(Debug, PartialEq, Eq)]
-struct Puzzle {
-    rows: usize,
-    cols: usize,
-    pts: HashMap<char, Vec<(i32, i32)>>,
-}
+use std::{env, error::Error, fs};
 
-impl Puzzle {
-    fn new(input: &str) -> Self {
-        let mut nodes: HashMap<char, Vec<(i32, i32)>> = HashMap::new();
-        let mut cols = 0;
-
-        for (x, line) in input.lines().enumerate() {
-            for (y, ch) in line.chars().enumerate() {
-                cols = line.chars().count();
-                if ch != '.' {
-                    nodes
-                        .entry(ch)
-                        .or_insert(Vec::new())
-                        .push((x as i32, y as i32));
-                }
-            }
-        }
+use _8::{
+    puzzle::Puzzle,
+    report::{render, Report},
+    rule::AntinodeRule,
+    space::Space,
+};
 
-        Puzzle {
-            pts: nodes,
-            rows: input.lines().count(),
-            cols,
-        }
-    }
+fn main() -> Result<(), Box<dyn Error>> {
+    let map = include_str!("../puzzle.txt");
+    let puzzle = Puzzle::new(map);
 
-    fn unique_antinodes(&self) -> Vec<(i32, i32)> {
-        let mut result = Vec::new();
-        for pts in self.pts.values() {
-            for pair in pts.iter().combinations(2) {
-                let [&a, &b] = pair[..] else { continue };
-                result.extend(antinodes(a, b, self.rows, self.cols));
+    // --report breaks antinodes down per frequency, --render draws the map,
+    // --only <frequency> limits the drawing to one frequency and --line uses
+    // the part 2 rule for both. --layers <file> counts both parts for a 3D
+    // volume of blank-line separated layers instead
+    let mut show_report = false;
+    let mut draw = false;
+    let mut only = None;
+    let mut rule = AntinodeRule::reflection();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--report" => show_report = true,
+            "--render" => draw = true,
+            "--line" => rule = AntinodeRule::line(),
+            "--only" => {
+                let frequency = args.next().ok_or("--only needs a frequency")?;
+                only = frequency.chars().next();
             }
-        }
-        result.sort();
-        result.dedup();
-        result
-    }
-
-    fn count_unique_antinodes(&self) -> usize {
-        self.unique_antinodes().iter().count()
-    }
-
-    fn unique_continuous_antinodes(&self) -> Vec<(i32, i32)> {
-        let mut result = Vec::new();
-        for pts in self.pts.values() {
-            for pair in pts.iter().combinations(2) {
-                let [&a, &b] = pair[..] else { continue };
-                result.extend(continuous_antinodes(a, b, self.rows, self.cols));
+            "--layers" => {
+                let path = args.next().ok_or("--layers needs a file")?;
+                let space = Space::from_layers(&fs::read_to_string(path)?);
+                println!(
+                    "result 1: {}",
+                    space.antinodes(&AntinodeRule::reflection()).len()
+                );
+                println!("result 2: {}", space.antinodes(&AntinodeRule::line()).len());
+                return Ok(());
             }
+            _ => return Err(format!("unknown argument {}", arg).into()),
         }
-        result.sort();
-        result.dedup();
-        result
-    }
-
-    fn count_unique_continuous_antinodes(&self) -> usize {
-        self.unique_continuous_antinodes().iter().count()
     }
-}
-
-fn continuous_antinodes(a: (i32, i32), b: (i32, i32), rows: usize, cols: usize) -> Vec<(i32, i32)> {
-    let (x0, y0) = (a.0, a.1);
-    let (x1, y1) = (b.0, b.1);
-
-    let mut dx = x1 - x0;
-    let mut dy = y1 - y0;
-
-    let gcd = gcd(dx.abs(), dy.abs());
-    dx /= gcd;
-    dy /= gcd;
-
-    let mut points = vec![a];
-
-    let mut current = (x1, y1);
-    while !is_within_bounds(current, rows, cols) {
-        points.push((current.0, current.1));
-        current.0 += dx;
-        current.1 += dy;
+    if show_report {
+        print!("{}", Report::new(&puzzle, &rule));
     }
-
-    current = (x0 - dx, y0 - dy);
-    while !is_within_bounds(current, rows, cols) {
-        points.push((current.0, current.1));
-        current.0 -= dx;
-        current.1 -= dy;
+    if draw {
+        println!("{}", render(&puzzle, &rule, only));
     }
 
-    points
-}
-
-fn gcd(mut a: i32, mut b: i32) -> i32 {
-    while b != 0 {
-        let temp = b;
-        b = a % b;
-        a = temp;
-    }
-    a
-}
-
-fn antinodes(a: (i32, i32), b: (i32, i32), rows: usize, cols: usize) -> Vec<(i32, i32)> {
-    let delta = (b.0 - a.0, b.1 - a.1);
-    let extended_back = (a.0 - delta.0, a.1 - delta.1);
-    let extended_front = (b.0 + delta.0, b.1 + delta.1);
-    vec![extended_back, extended_front]
-        .into_iter()
-        .filter(|&pt| !is_within_bounds(pt, rows, cols))
-        .collect()
-}
-
-fn is_within_bounds(pt: (i32, i32), rows: usize, cols: usize) -> bool {
-    pt.0 < 0 || pt.1 < 0 || pt.0 as usize >= rows || pt.1 as usize >= cols
-}
-
-fn main() {
-    let map = include_str!("../puzzle.txt");
-    let puzzle = Puzzle::new(map);
     let count = puzzle.count_unique_antinodes();
     let count_continuous = puzzle.count_unique_continuous_antinodes();
     println!("result 1: {}", count);
     println!("result 2: {}", count_continuous);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use _8::{
+        bounds::Bounds,
+        puzzle::{antinodes, continuous_antinodes},
+    };
+
     use super::*;
 
     #[test]
@@ -190,7 +123,7 @@ mod tests {
     #[test]
     fn should_generate_continuous_line() {
         let expected = 5;
-        let actual = continuous_antinodes((0, 0), (2, 1), 10, 10).iter().count();
+        let actual = continuous_antinodes((0, 0), (2, 1), 10, 10).len();
         assert_eq!(expected, actual);
     }
 
@@ -231,4 +164,27 @@ mod tests {
         let actual = Puzzle::new(input).count_unique_continuous_antinodes();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn should_respect_non_square_bounds() {
+        let wide = "............
+..a..a......
+............";
+        let puzzle = Puzzle::new(wide);
+        assert_eq!(puzzle.bounds, Bounds::new(3, 12));
+        assert_eq!(puzzle.unique_antinodes(), vec![(1, 8)]);
+
+        let tall = "...\n.a.\n...\n...\n.a.\n...\n...\n...\n...\n...";
+        let puzzle = Puzzle::new(tall);
+        assert_eq!(puzzle.bounds, Bounds::new(10, 3));
+        assert_eq!(puzzle.unique_antinodes(), vec![(7, 1)]);
+        assert_eq!(puzzle.unique_continuous_antinodes().len(), 10);
+    }
+
+    #[test]
+    fn should_size_ragged_map() {
+        let puzzle = Puzzle::new(".....\na.a\n.\n\n");
+        assert_eq!(puzzle.bounds, Bounds::new(3, 5));
+        assert_eq!(puzzle.unique_antinodes(), vec![(1, 4)]);
+    }
 }
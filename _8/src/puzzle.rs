@@ -0,0 +1,98 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use crate::{bounds::Bounds, rule::AntinodeRule};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub bounds: Bounds,
+    pub pts: HashMap<char, Vec<(i32, i32)>>,
+}
+
+impl Puzzle {
+    // The map is as wide as its widest line, shorter lines are treated as
+    // padded with empty cells. Blank lines at the end don't count as rows.
+    pub fn new(input: &str) -> Self {
+        let mut nodes: HashMap<char, Vec<(i32, i32)>> = HashMap::new();
+        let lines: Vec<&str> = input.trim_end_matches(['\n', '\r']).lines().collect();
+
+        for (x, line) in lines.iter().enumerate() {
+            for (y, ch) in line.chars().enumerate() {
+                if ch != '.' {
+                    nodes.entry(ch).or_default().push((x as i32, y as i32));
+                }
+            }
+        }
+
+        Puzzle {
+            pts: nodes,
+            bounds: Bounds::new(
+                lines.len(),
+                lines.iter().map(|l| l.chars().count()).max().unwrap_or(0),
+            ),
+        }
+    }
+
+    // every distinct antinode of every group the rule pairs antennas in
+    pub fn antinodes(&self, rule: &AntinodeRule) -> Vec<(i32, i32)> {
+        let mut result: Vec<(i32, i32)> = rule
+            .grouping
+            .groups(&self.pts)
+            .values()
+            .flat_map(|pts| self.group_antinodes(pts, rule))
+            .collect();
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    // distinct antinodes of one group of antennas, sorted
+    pub fn group_antinodes(&self, pts: &[(i32, i32)], rule: &AntinodeRule) -> Vec<(i32, i32)> {
+        let mut result = Vec::new();
+        for pair in pts.iter().combinations(2) {
+            let [&a, &b] = pair[..] else { continue };
+            result.extend(rule.points(a, b, &self.bounds));
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    pub fn unique_antinodes(&self) -> Vec<(i32, i32)> {
+        self.antinodes(&AntinodeRule::reflection())
+    }
+
+    pub fn count_unique_antinodes(&self) -> usize {
+        self.unique_antinodes().len()
+    }
+
+    pub fn unique_continuous_antinodes(&self) -> Vec<(i32, i32)> {
+        self.antinodes(&AntinodeRule::line())
+    }
+
+    pub fn count_unique_continuous_antinodes(&self) -> usize {
+        self.unique_continuous_antinodes().len()
+    }
+}
+
+pub fn continuous_antinodes(
+    a: (i32, i32),
+    b: (i32, i32),
+    rows: usize,
+    cols: usize,
+) -> Vec<(i32, i32)> {
+    AntinodeRule::line().points(a, b, &Bounds::new(rows, cols))
+}
+
+pub fn gcd(mut a: i32, mut b: i32) -> i32 {
+    while b != 0 {
+        let temp = b;
+        b = a % b;
+        a = temp;
+    }
+    a
+}
+
+pub fn antinodes(a: (i32, i32), b: (i32, i32), rows: usize, cols: usize) -> Vec<(i32, i32)> {
+    AntinodeRule::reflection().points(a, b, &Bounds::new(rows, cols))
+}
@@ -0,0 +1,161 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{puzzle::Puzzle, rule::AntinodeRule};
+
+// Antinodes kept apart by the group that produced them, groups are the
+// rule's grouping keys (the frequency itself unless grouped by class).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub per_frequency: BTreeMap<char, Vec<(i32, i32)>>,
+    // cells more than one group puts an antinode on
+    pub shared: BTreeMap<(i32, i32), Vec<char>>,
+}
+
+impl Report {
+    pub fn new(puzzle: &Puzzle, rule: &AntinodeRule) -> Self {
+        let per_frequency: BTreeMap<char, Vec<(i32, i32)>> = rule
+            .grouping
+            .groups(&puzzle.pts)
+            .into_iter()
+            .map(|(key, pts)| (key, puzzle.group_antinodes(&pts, rule)))
+            .collect();
+
+        let mut by_cell: BTreeMap<(i32, i32), Vec<char>> = BTreeMap::new();
+        for (&key, pts) in &per_frequency {
+            for &pt in pts {
+                by_cell.entry(pt).or_default().push(key);
+            }
+        }
+        by_cell.retain(|_, keys| keys.len() > 1);
+
+        Report {
+            per_frequency,
+            shared: by_cell,
+        }
+    }
+
+    pub fn count(&self, frequency: char) -> usize {
+        self.per_frequency
+            .get(&frequency)
+            .map_or(0, |pts| pts.len())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, pts) in &self.per_frequency {
+            writeln!(f, "{}: {} antinodes", key, pts.len())?;
+        }
+        for ((x, y), keys) in &self.shared {
+            let keys: String = keys.iter().collect();
+            writeln!(f, "({}, {}) shared by {}", x, y, keys)?;
+        }
+        Ok(())
+    }
+}
+
+// The map the way the puzzle draws it: antennas by their frequency, `#` on
+// antinodes without an antenna. With `only`, just that frequency's group.
+pub fn render(puzzle: &Puzzle, rule: &AntinodeRule, only: Option<char>) -> String {
+    let keep = |ch: char| only.is_none_or(|c| rule.grouping.key(c) == rule.grouping.key(ch));
+    let antennas: HashMap<(i32, i32), char> = puzzle
+        .pts
+        .iter()
+        .filter(|&(&ch, _)| keep(ch))
+        .flat_map(|(&ch, pts)| pts.iter().map(move |&pt| (pt, ch)))
+        .collect();
+    let antinodes: Vec<(i32, i32)> = rule
+        .grouping
+        .groups(&puzzle.pts)
+        .into_iter()
+        .filter(|&(key, _)| keep(key))
+        .flat_map(|(_, pts)| puzzle.group_antinodes(&pts, rule))
+        .collect();
+
+    let mut grid = vec![vec!['.'; puzzle.bounds.cols]; puzzle.bounds.rows];
+    for (x, y) in antinodes {
+        grid[x as usize][y as usize] = '#';
+    }
+    for (&(x, y), &ch) in &antennas {
+        grid[x as usize][y as usize] = ch;
+    }
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+    #[test]
+    fn should_count_per_frequency() {
+        let report = Report::new(&Puzzle::new(EXAMPLE), &AntinodeRule::reflection());
+        assert_eq!(report.count('0'), 10);
+        assert_eq!(report.count('A'), 5);
+        assert_eq!(report.count('x'), 0);
+        // 10 + 5 antinodes but only 14 distinct cells
+        assert_eq!(report.shared, BTreeMap::from([((1, 3), vec!['0', 'A'])]));
+        assert_eq!(
+            report.to_string(),
+            "0: 10 antinodes\nA: 5 antinodes\n(1, 3) shared by 0A\n"
+        );
+    }
+
+    #[test]
+    fn should_render_like_puzzle() {
+        let expected = "......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.";
+        let actual = render(&Puzzle::new(EXAMPLE), &AntinodeRule::reflection(), None);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn should_render_one_frequency() {
+        let expected = "............
+...#........
+....#.......
+............
+............
+......A.....
+............
+.......#....
+........A...
+.........A..
+..........#.
+..........#.";
+        let actual = render(
+            &Puzzle::new(EXAMPLE),
+            &AntinodeRule::reflection(),
+            Some('A'),
+        );
+        assert_eq!(expected, actual);
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use crate::{bounds::Bounds, puzzle::gcd};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Spacing {
+    // points in line with the pair where one antenna is k times as far away
+    // as the other, for every k given
+    Ratios(Vec<i32>),
+    // every grid point in line with the pair
+    Line,
+}
+
+// Which antennas get paired up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    // the same character, as in the puzzle
+    Frequency,
+    // digits, lowercase and uppercase letters each form one group
+    Class,
+}
+
+impl Grouping {
+    pub fn key(&self, ch: char) -> char {
+        match self {
+            Grouping::Frequency => ch,
+            Grouping::Class if ch.is_ascii_digit() => '0',
+            Grouping::Class if ch.is_lowercase() => 'a',
+            Grouping::Class if ch.is_uppercase() => 'A',
+            Grouping::Class => ch,
+        }
+    }
+
+    pub fn groups<T: Copy>(&self, antennas: &HashMap<char, Vec<T>>) -> HashMap<char, Vec<T>> {
+        let mut result: HashMap<char, Vec<T>> = HashMap::new();
+        for (&ch, pts) in antennas {
+            result.entry(self.key(ch)).or_default().extend(pts);
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AntinodeRule {
+    pub spacing: Spacing,
+    // only points between the two antennas instead of beyond them
+    pub between: bool,
+    // Chebyshev distance to the nearer antenna
+    pub max_range: Option<i32>,
+    pub grouping: Grouping,
+}
+
+impl AntinodeRule {
+    // part 1: one antenna twice as far as the other, outside the pair
+    pub fn reflection() -> Self {
+        AntinodeRule {
+            spacing: Spacing::Ratios(vec![2]),
+            between: false,
+            max_range: None,
+            grouping: Grouping::Frequency,
+        }
+    }
+
+    // part 2: anywhere on the line, antennas included
+    pub fn line() -> Self {
+        AntinodeRule {
+            spacing: Spacing::Line,
+            ..Self::reflection()
+        }
+    }
+
+    pub fn points(&self, a: (i32, i32), b: (i32, i32), bounds: &Bounds) -> Vec<(i32, i32)> {
+        self.points_in([a.0, a.1], [b.0, b.1], &[bounds.rows, bounds.cols])
+            .into_iter()
+            .map(|[x, y]| (x, y))
+            .collect()
+    }
+
+    // The same for any number of dimensions, `size` is the extent along each
+    // axis and the line is stepped by the gcd of every component.
+    pub fn points_in<const N: usize>(
+        &self,
+        a: [i32; N],
+        b: [i32; N],
+        size: &[usize; N],
+    ) -> Vec<[i32; N]> {
+        let contains = |pt: &[i32; N]| {
+            pt.iter()
+                .zip(size)
+                .all(|(&c, &s)| c >= 0 && (c as usize) < s)
+        };
+        let delta: [i32; N] = std::array::from_fn(|i| b[i] - a[i]);
+        let mut result = Vec::new();
+
+        match &self.spacing {
+            // the same point twice has no direction, only itself is on it
+            Spacing::Line if delta == [0; N] => result.push(a),
+            Spacing::Line => {
+                let g = delta.iter().fold(0, |g, d| gcd(g, d.abs()));
+                let step: [i32; N] = std::array::from_fn(|i| delta[i] / g);
+                let at = |from: [i32; N], k: i32| -> [i32; N] {
+                    std::array::from_fn(|i| from[i] + k * step[i])
+                };
+                if self.between {
+                    result.extend((0..=g).map(|k| at(a, k)));
+                } else {
+                    for (sign, start) in [(1, a), (-1, at(a, -1))] {
+                        let mut current = start;
+                        while contains(&current) {
+                            result.push(current);
+                            current = at(current, sign);
+                        }
+                    }
+                }
+            }
+            // With P = a + t * (b - a), |P - a| = k |P - b| gives t = k / (k + 1)
+            // between the antennas and t = k / (k - 1) beyond b, and the
+            // same again with a and b swapped. Only whole grid points count.
+            Spacing::Ratios(ks) => {
+                for &k in ks {
+                    let (nums, den) = if self.between {
+                        ([1, k], k + 1)
+                    } else {
+                        ([-1, k], k - 1)
+                    };
+                    if den <= 0 {
+                        continue;
+                    }
+                    // k = 1 between the antennas is the midpoint from both sides
+                    let nums = if self.between && k == 1 {
+                        &nums[..1]
+                    } else {
+                        &nums[..]
+                    };
+                    for &num in nums {
+                        if delta.iter().all(|d| d * num % den == 0) {
+                            result.push(std::array::from_fn(|i| a[i] + delta[i] * num / den));
+                        }
+                    }
+                }
+            }
+        }
+
+        result.retain(|pt| contains(pt) && self.in_range(pt, &a, &b));
+        result
+    }
+
+    fn in_range<const N: usize>(&self, pt: &[i32; N], a: &[i32; N], b: &[i32; N]) -> bool {
+        let distance = |q: &[i32; N]| {
+            pt.iter()
+                .zip(q)
+                .map(|(p, q)| (p - q).abs())
+                .max()
+                .unwrap_or(0)
+        };
+        self.max_range
+            .is_none_or(|range| distance(a).min(distance(b)) <= range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_place_reflections() {
+        let rule = AntinodeRule::reflection();
+        assert_eq!(
+            rule.points((3, 4), (5, 5), &Bounds::new(10, 10)),
+            vec![(1, 3), (7, 6)]
+        );
+    }
+
+    #[test]
+    fn should_place_other_ratios() {
+        let rule = AntinodeRule {
+            spacing: Spacing::Ratios(vec![2, 3]),
+            ..AntinodeRule::reflection()
+        };
+        // k = 3 lands half a step past each antenna, only whole deltas count
+        assert_eq!(
+            rule.points((4, 4), (6, 6), &Bounds::new(12, 12)),
+            vec![(2, 2), (8, 8), (3, 3), (7, 7)]
+        );
+        assert_eq!(
+            rule.points((4, 4), (5, 5), &Bounds::new(12, 12)),
+            vec![(3, 3), (6, 6)]
+        );
+    }
+
+    #[test]
+    fn should_place_between() {
+        let rule = AntinodeRule {
+            between: true,
+            ..AntinodeRule::reflection()
+        };
+        assert_eq!(
+            rule.points((0, 0), (3, 6), &Bounds::new(10, 10)),
+            vec![(1, 2), (2, 4)]
+        );
+        assert!(rule.points((0, 0), (1, 2), &Bounds::new(10, 10)).is_empty());
+
+        let rule = AntinodeRule {
+            between: true,
+            ..AntinodeRule::line()
+        };
+        assert_eq!(
+            rule.points((0, 0), (2, 4), &Bounds::new(10, 10)),
+            vec![(0, 0), (1, 2), (2, 4)]
+        );
+    }
+
+    #[test]
+    fn should_handle_degenerate_pairs() {
+        let bounds = Bounds::new(10, 10);
+        assert_eq!(
+            AntinodeRule::line().points((3, 4), (3, 4), &bounds),
+            vec![(3, 4)]
+        );
+
+        let rule = AntinodeRule {
+            spacing: Spacing::Ratios(vec![1]),
+            between: true,
+            ..AntinodeRule::reflection()
+        };
+        assert_eq!(rule.points((2, 2), (4, 6), &bounds), vec![(3, 4)]);
+    }
+
+    #[test]
+    fn should_limit_range() {
+        let rule = AntinodeRule {
+            max_range: Some(2),
+            ..AntinodeRule::line()
+        };
+        let mut actual = rule.points((5, 0), (5, 1), &Bounds::new(10, 10));
+        actual.sort();
+        assert_eq!(actual, vec![(5, 0), (5, 1), (5, 2), (5, 3)]);
+    }
+
+    #[test]
+    fn should_group_by_class() {
+        let antennas = HashMap::from([
+            ('a', vec![(0, 0)]),
+            ('b', vec![(1, 1)]),
+            ('A', vec![(2, 2)]),
+            ('7', vec![(3, 3)]),
+        ]);
+        let groups = Grouping::Class.groups(&antennas);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&'a'].len(), 2);
+        assert_eq!(Grouping::Frequency.groups(&antennas).len(), 4);
+    }
+}
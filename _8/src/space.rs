@@ -0,0 +1,138 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use crate::rule::AntinodeRule;
+
+// Antennas in a volume of any dimension, `size` is the extent along each axis.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Space<const N: usize> {
+    pub size: [usize; N],
+    pub antennas: HashMap<char, Vec<[i32; N]>>,
+}
+
+impl<const N: usize> Space<N> {
+    // every distinct antinode of every group the rule pairs antennas in
+    pub fn antinodes(&self, rule: &AntinodeRule) -> Vec<[i32; N]> {
+        let mut result: Vec<[i32; N]> = Vec::new();
+        for pts in rule.grouping.groups(&self.antennas).values() {
+            for pair in pts.iter().combinations(2) {
+                let [&a, &b] = pair[..] else { continue };
+                result.extend(rule.points_in(a, b, &self.size));
+            }
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+}
+
+impl Space<3> {
+    // 2D maps separated by blank lines, one per layer, so a point is
+    // (layer, row, col). Each axis is as long as its longest extent. Any
+    // number of blank lines makes one break, and CRLF works like LF.
+    pub fn from_layers(input: &str) -> Self {
+        let mut antennas: HashMap<char, Vec<[i32; 3]>> = HashMap::new();
+        let mut size = [0; 3];
+
+        let layers = input
+            .lines()
+            .chunk_by(|line| line.is_empty())
+            .into_iter()
+            .filter(|(blank, _)| !blank)
+            .map(|(_, layer)| layer.collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for (z, layer) in layers.iter().enumerate() {
+            size[0] = z + 1;
+            for (x, line) in layer.iter().enumerate() {
+                size[1] = size[1].max(x + 1);
+                size[2] = size[2].max(line.chars().count());
+                for (y, ch) in line.chars().enumerate() {
+                    if ch != '.' {
+                        antennas
+                            .entry(ch)
+                            .or_default()
+                            .push([z as i32, x as i32, y as i32]);
+                    }
+                }
+            }
+        }
+
+        Space { size, antennas }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+    #[test]
+    fn should_match_flat_map() {
+        let space = Space::from_layers(EXAMPLE);
+        assert_eq!(space.size, [1, 12, 12]);
+        assert_eq!(space.antinodes(&AntinodeRule::reflection()).len(), 14);
+        assert_eq!(space.antinodes(&AntinodeRule::line()).len(), 34);
+    }
+
+    #[test]
+    fn should_parse_layers() {
+        let space = Space::from_layers("a..\n...\n...\n\n...\n.a.\n...\n\n...\n...\n...\n");
+        assert_eq!(space.size, [3, 3, 3]);
+        assert_eq!(space.antennas[&'a'], vec![[0, 0, 0], [1, 1, 1]]);
+        assert_eq!(
+            space.antinodes(&AntinodeRule::reflection()),
+            vec![[2, 2, 2]]
+        );
+        assert_eq!(
+            space.antinodes(&AntinodeRule::line()),
+            vec![[0, 0, 0], [1, 1, 1], [2, 2, 2]]
+        );
+    }
+
+    #[test]
+    fn should_split_layers_on_any_blank_lines() {
+        let lf = Space::from_layers("\na..\n...\n\n\n\n...\n.a.\n\n");
+        assert_eq!(lf.size, [2, 2, 3]);
+        assert_eq!(lf.antennas[&'a'], vec![[0, 0, 0], [1, 1, 1]]);
+        let crlf = Space::from_layers("a..\r\n...\r\n\r\n...\r\n.a.\r\n");
+        assert_eq!(crlf, lf);
+    }
+
+    #[test]
+    fn should_step_by_gcd() {
+        let rule = AntinodeRule {
+            between: true,
+            ..AntinodeRule::line()
+        };
+        assert_eq!(
+            rule.points_in([0, 0, 0], [2, 2, 4], &[3, 3, 5]),
+            vec![[0, 0, 0], [1, 1, 2], [2, 2, 4]]
+        );
+        // no whole grid point strictly between when the components are coprime
+        assert_eq!(
+            rule.points_in([0, 0, 0], [1, 2, 3], &[3, 3, 5]),
+            vec![[0, 0, 0], [1, 2, 3]]
+        );
+    }
+
+    #[test]
+    fn should_work_in_four_dimensions() {
+        let space = Space {
+            size: [4; 4],
+            antennas: HashMap::from([('x', vec![[0, 0, 0, 0], [1, 1, 1, 1]])]),
+        };
+        assert_eq!(space.antinodes(&AntinodeRule::line()).len(), 4);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{
+    disk::{Disk, Span},
+    free::FreeSpans,
+};
+
+// One file moving, and what the disk looks like afterwards in the puzzle's
+// notation, unless the disk is too long to render.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub id: usize,
+    pub from: Span,
+    pub to: Span,
+    pub layout: Option<String>,
+}
+
+fn record(log: &mut Option<&mut Vec<Step>>, disk: &Disk, id: usize, from: Span, to: Span) {
+    if let Some(log) = log.as_deref_mut() {
+        log.push(Step {
+            id,
+            from,
+            to,
+            layout: disk.render(),
+        });
+    }
+}
+
+// Part 1: the last file block moves to the leftmost free block, over and
+// over, until there is no free block left of a file block. Files end up in
+// pieces. Runs of blocks move together unless there is a log, which gets
+// one step per block like the puzzle shows it.
+pub fn blocks(disk: &Disk, mut log: Option<&mut Vec<Step>>) -> Disk {
+    let mut pieces: Vec<(usize, Span)> = disk
+        .files
+        .iter()
+        .enumerate()
+        .flat_map(|(id, spans)| spans.iter().map(move |&span| (id, span)))
+        .filter(|(_, span)| span.len > 0)
+        .collect();
+    pieces.sort_by_key(|&(_, span)| span);
+    let mut moved: Vec<(usize, Span)> = Vec::new();
+    let free = disk.free();
+    let mut free = free.iter().copied().peekable();
+
+    let assemble = |pieces: &[(usize, Span)], moved: &[(usize, Span)]| {
+        let mut files = vec![Vec::new(); disk.files.len()];
+        for &(id, span) in pieces.iter().chain(moved) {
+            files[id].push(span);
+        }
+        let mut result = Disk {
+            len: disk.len,
+            files,
+        };
+        result.merge();
+        result
+    };
+
+    while let (Some(&(id, piece)), Some(hole)) = (pieces.last(), free.peek_mut()) {
+        if hole.pos > piece.pos {
+            break;
+        }
+        let n = if log.is_some() {
+            1
+        } else {
+            hole.len.min(piece.len)
+        };
+        let from = Span {
+            pos: piece.end() - n,
+            len: n,
+        };
+        let to = Span {
+            pos: hole.pos,
+            len: n,
+        };
+        moved.push((id, to));
+        hole.pos += n;
+        hole.len -= n;
+        if hole.len == 0 {
+            free.next();
+        }
+        let last = pieces.len() - 1;
+        pieces[last].1.len -= n;
+        if pieces[last].1.len == 0 {
+            pieces.pop();
+        }
+        if log.is_some() {
+            record(&mut log, &assemble(&pieces, &moved), id, from, to);
+        }
+    }
+    assemble(&pieces, &moved)
+}
+
+// Where a whole file goes among the free spans left of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    // the leftmost one it fits in, as in the puzzle
+    First,
+    // the shortest one it fits in
+    Best,
+    // the longest one
+    Worst,
+}
+
+// Part 2: every file, highest id first, moves once to the leftmost free span
+// it fits in, if that is left of where it is.
+pub fn whole_files(disk: &Disk) -> Disk {
+    fit_files(disk, Fit::First, None)
+}
+
+// The same, also returning every move made
+pub fn whole_files_logged(disk: &Disk) -> (Disk, Vec<Step>) {
+    let mut log = Vec::new();
+    let result = fit_files(disk, Fit::First, Some(&mut log));
+    (result, log)
+}
+
+// Every file, highest id first, moves once to the free span `fit` picks, if
+// any span left of it is long enough. Ties go to the leftmost span.
+pub fn fit_files(disk: &Disk, fit: Fit, log: Option<&mut Vec<Step>>) -> Disk {
+    fit_pass(disk, fit, log).0
+}
+
+// The free spans go in a `FreeSpans` index, what is left of a span after a
+// move stays in it. The space a file leaves behind is not reused, on a
+// freshly parsed disk every file still to come is further left anyway.
+fn fit_pass(disk: &Disk, fit: Fit, mut log: Option<&mut Vec<Step>>) -> (Disk, usize) {
+    let mut free = FreeSpans::new(disk.free(), fit == Fit::Best);
+
+    let mut result = disk.clone();
+    let mut moves = 0;
+    for id in (0..result.files.len()).rev() {
+        let spans = &result.files[id];
+        let (Some(&first), len) = (spans.first(), spans.iter().map(|span| span.len).sum()) else {
+            continue;
+        };
+        if len == 0 {
+            continue;
+        }
+        let found = match fit {
+            Fit::First => free.first_fit(len, first.pos),
+            Fit::Best => free.best_fit(len, first.pos),
+            Fit::Worst => free.worst_fit(len, first.pos),
+        };
+        let Some(i) = found else {
+            continue;
+        };
+
+        let to = free.take(i, len);
+        result.files[id] = vec![to];
+        moves += 1;
+        let from = Span {
+            pos: first.pos,
+            len,
+        };
+        record(&mut log, &result, id, from, to);
+    }
+    (result, moves)
+}
+
+// First fit over and over until no file moves. A file that found no room
+// can move once files left of it have made some, and one that moved can
+// move again further left.
+pub fn repeated(disk: &Disk, mut log: Option<&mut Vec<Step>>) -> Disk {
+    let mut result = disk.clone();
+    loop {
+        let (next, moves) = fit_pass(&result, Fit::First, log.as_deref_mut());
+        if moves == 0 {
+            return next;
+        }
+        result = next;
+    }
+}
+
+// Every file in one piece, packed from the start in the order they are on
+// disk, so all the free space ends up after the last file.
+pub fn defragment(disk: &Disk, mut log: Option<&mut Vec<Step>>) -> Disk {
+    let mut order: Vec<(Span, usize)> = disk
+        .files
+        .iter()
+        .enumerate()
+        .filter_map(|(id, spans)| Some((*spans.first()?, id)))
+        .collect();
+    order.sort();
+
+    let mut result = disk.clone();
+    let mut pos = 0;
+    for (first, id) in order {
+        let len = result.files[id].iter().map(|span| span.len).sum();
+        let to = Span { pos, len };
+        pos += len;
+        if result.files[id] == [to] {
+            continue;
+        }
+        result.files[id] = vec![to];
+        let from = Span {
+            pos: first.pos,
+            len,
+        };
+        record(&mut log, &result, id, from, to);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compact_example() {
+        let disk = whole_files(&Disk::from_dense("2333133121414131402").unwrap());
+        assert_eq!(disk.checksum(), 2858);
+        // 00992111777.44.333....5555.6666.....8888..
+        assert_eq!(disk.files[9], vec![Span { pos: 2, len: 2 }]);
+        assert_eq!(disk.files[2], vec![Span { pos: 4, len: 1 }]);
+        assert_eq!(disk.files[7], vec![Span { pos: 8, len: 3 }]);
+        assert_eq!(disk.files[4], vec![Span { pos: 12, len: 2 }]);
+        assert_eq!(disk.files[8], vec![Span { pos: 36, len: 4 }]);
+    }
+
+    #[test]
+    fn should_not_move_files_right() {
+        // 0.11 -> 011. but 1 doesn't fit, and 0 is already leftmost
+        let disk = Disk::from_dense("1120").unwrap();
+        assert_eq!(whole_files(&disk), disk);
+        let disk = whole_files(&Disk::from_dense("191").unwrap());
+        assert_eq!(disk.files[1], vec![Span { pos: 1, len: 1 }]);
+    }
+
+    #[test]
+    fn should_use_long_free_spans() {
+        // one long span taken up bit by bit
+        let disk = whole_files(&Disk::from_dense("1912121").unwrap());
+        assert_eq!(disk.files[3], vec![Span { pos: 1, len: 1 }]);
+        assert_eq!(disk.files[2], vec![Span { pos: 2, len: 1 }]);
+        assert_eq!(disk.files[1], vec![Span { pos: 3, len: 1 }]);
+        assert_eq!(disk.free(), vec![Span { pos: 4, len: 13 }]);
+    }
+
+    #[test]
+    fn should_log_example_moves() {
+        let (_, log) = whole_files_logged(&Disk::from_dense("2333133121414131402").unwrap());
+        let layouts: Vec<&str> = log
+            .iter()
+            .map(|step| step.layout.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            layouts,
+            vec![
+                "0099.111...2...333.44.5555.6666.777.8888..",
+                "0099.1117772...333.44.5555.6666.....8888..",
+                "0099.111777244.333....5555.6666.....8888..",
+                "00992111777.44.333....5555.6666.....8888..",
+            ]
+        );
+        assert_eq!(
+            log[0],
+            Step {
+                id: 9,
+                from: Span { pos: 40, len: 2 },
+                to: Span { pos: 2, len: 2 },
+                layout: Some(layouts[0].to_string()),
+            }
+        );
+        assert_eq!(
+            log.iter().map(|step| step.id).collect::<Vec<_>>(),
+            vec![9, 7, 4, 2]
+        );
+    }
+
+    #[test]
+    fn should_log_huge_disks_without_layouts() {
+        let disk = Disk::from_extended("1,0,1000000000,5,1").unwrap();
+        let (result, log) = whole_files_logged(&disk);
+        assert_eq!(result.files[2], vec![Span { pos: 1_000_000_001, len: 1 }]);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].layout, None);
+    }
+
+    // pseudo-random extents below `max` in the extended format
+    fn random_extended(files: usize, max: u64, mut seed: u64) -> String {
+        (0..2 * files - 1)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                (seed % max).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    // every free span checked for every file
+    fn scan(disk: &Disk, fit: Fit) -> Disk {
+        let mut free = disk.free();
+        let mut result = disk.clone();
+        for id in (0..result.files.len()).rev() {
+            let Some(&first) = result.files[id].first() else {
+                continue;
+            };
+            if first.len == 0 {
+                continue;
+            }
+            let fits = free
+                .iter()
+                .enumerate()
+                .filter(|(_, span)| span.pos < first.pos && span.len >= first.len);
+            let found = match fit {
+                Fit::First => fits.min_by_key(|&(i, _)| i),
+                Fit::Best => fits.min_by_key(|&(i, span)| (span.len, i)),
+                Fit::Worst => fits.min_by_key(|&(i, span)| (std::cmp::Reverse(span.len), i)),
+            };
+            if let Some((i, _)) = found {
+                result.files[id] = vec![Span {
+                    pos: free[i].pos,
+                    len: first.len,
+                }];
+                free[i].pos += first.len;
+                free[i].len -= first.len;
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn should_agree_with_scan() {
+        for (max, seed) in [(10, 1), (1_000, 2), (1_000_000, 3)] {
+            let disk = Disk::from_extended(&random_extended(1_000, max, seed)).unwrap();
+            for fit in [Fit::First, Fit::Best, Fit::Worst] {
+                assert_eq!(fit_files(&disk, fit, None), scan(&disk, fit), "{:?}", fit);
+            }
+        }
+    }
+
+    // cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn should_scale_with_large_extents() {
+        let time = |files: usize| {
+            let disk = Disk::from_extended(&random_extended(files, 1_000_000, 99)).unwrap();
+            let start = std::time::Instant::now();
+            for fit in [Fit::First, Fit::Best, Fit::Worst] {
+                fit_files(&disk, fit, None);
+            }
+            start.elapsed()
+        };
+        let small = time(5_000);
+        let large = time(20_000);
+        // 4 times the files, quadratic would take 16 times as long
+        assert!(
+            large < small * 8 + std::time::Duration::from_millis(50),
+            "{:?} for 5000 files, {:?} for 20000",
+            small,
+            large
+        );
+        assert!(large.as_secs() < 5, "took {:?}", large);
+    }
+}
@@ -0,0 +1,384 @@
+use std::{error::Error, fmt};
+
+// longest disk `Disk::render` writes out, one character per block
+pub const MAX_RENDER: usize = 1 << 20;
+
+// A run of blocks starting at `pos`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+    pub pos: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn end(&self) -> usize {
+        self.pos + self.len
+    }
+}
+
+// Where every file sits, `files[id]` holds its spans in order. Everything
+// not covered by a file up to `len` is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    pub len: usize,
+    pub files: Vec<Vec<Span>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // characters that have no place in the map, with their offsets
+    Stray(Vec<(usize, char)>),
+    // a length starting at `offset` that is missing or too big
+    BadLength { offset: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Stray(stray) => {
+                let stray: Vec<String> = stray
+                    .iter()
+                    .map(|(offset, ch)| format!("{:?} at offset {}", ch, offset))
+                    .collect();
+                write!(f, "unexpected {}", stray.join(", "))
+            }
+            ParseError::BadLength { offset } => write!(f, "bad length at offset {}", offset),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl Disk {
+    // The puzzle's dense format, file and free span lengths alternating, one
+    // digit each. Anything but digits and trailing whitespace is rejected.
+    pub fn from_dense(input: &str) -> Result<Self, ParseError> {
+        let body = input.trim_end();
+        let stray: Vec<(usize, char)> = body
+            .char_indices()
+            .filter(|(_, ch)| !ch.is_ascii_digit())
+            .collect();
+        if !stray.is_empty() {
+            return Err(ParseError::Stray(stray));
+        }
+        Self::from_lengths(
+            body.bytes()
+                .enumerate()
+                .map(|(offset, b)| (offset, Some((b - b'0') as usize))),
+        )
+    }
+
+    // The same lengths as integers of any size with commas in between, like
+    // `2,3,3,3,1,3`.
+    pub fn from_extended(input: &str) -> Result<Self, ParseError> {
+        let body = input.trim_end();
+        let stray: Vec<(usize, char)> = body
+            .char_indices()
+            .filter(|&(_, ch)| !ch.is_ascii_digit() && ch != ',')
+            .collect();
+        if !stray.is_empty() {
+            return Err(ParseError::Stray(stray));
+        }
+        if body.is_empty() {
+            return Self::from_lengths([].into_iter());
+        }
+        let mut offset = 0;
+        let lengths = body.split(',').map(|field| {
+            let start = offset;
+            offset += field.len() + 1;
+            (start, field.parse().ok())
+        });
+        Self::from_lengths(lengths)
+    }
+
+    fn from_lengths(
+        lengths: impl Iterator<Item = (usize, Option<usize>)>,
+    ) -> Result<Self, ParseError> {
+        let mut files = Vec::new();
+        let mut pos: usize = 0;
+        for (i, (offset, len)) in lengths.enumerate() {
+            let len = len.ok_or(ParseError::BadLength { offset })?;
+            if i % 2 == 0 {
+                files.push(vec![Span { pos, len }]);
+            }
+            pos = pos
+                .checked_add(len)
+                .ok_or(ParseError::BadLength { offset })?;
+        }
+        Ok(Disk { len: pos, files })
+    }
+
+    // each file's spans in order, touching ones joined
+    pub fn merge(&mut self) {
+        for spans in &mut self.files {
+            spans.sort();
+            let mut merged: Vec<Span> = Vec::with_capacity(spans.len());
+            for &span in spans.iter() {
+                match merged.last_mut() {
+                    Some(last) if last.end() == span.pos => last.len += span.len,
+                    _ => merged.push(span),
+                }
+            }
+            *spans = merged;
+        }
+    }
+
+    // gaps between files, left to right
+    pub fn free(&self) -> Vec<Span> {
+        let mut used: Vec<Span> = self.files.iter().flatten().copied().collect();
+        used.sort();
+        let mut result = Vec::new();
+        let mut pos = 0;
+        for span in used.iter().chain(&[Span {
+            pos: self.len,
+            len: 0,
+        }]) {
+            if span.pos > pos {
+                result.push(Span {
+                    pos,
+                    len: span.pos - pos,
+                });
+            }
+            pos = pos.max(span.end());
+        }
+        result
+    }
+
+    // The puzzle's notation, one character per block: `.` when free, else
+    // the file's id. Ids past 9 don't fit in one character and are written
+    // in parentheses, `(12)`. None past MAX_RENDER blocks.
+    pub fn render(&self) -> Option<String> {
+        if self.len > MAX_RENDER {
+            return None;
+        }
+        let mut owners: Vec<Option<usize>> = vec![None; self.len];
+        for (id, spans) in self.files.iter().enumerate() {
+            for span in spans {
+                owners[span.pos..span.end()].fill(Some(id));
+            }
+        }
+        owners
+            .iter()
+            .map(|owner| match owner {
+                None => ".".to_string(),
+                Some(id) if *id < 10 => id.to_string(),
+                Some(id) => format!("({})", id),
+            })
+            .collect::<String>()
+            .into()
+    }
+
+    // File and free lengths alternating, left to right, the way the dense
+    // format lists them. Files are numbered by position there, so a file in
+    // pieces comes out as one file per piece and ids change once files
+    // have moved past each other.
+    pub fn lengths(&self) -> Vec<usize> {
+        let mut used: Vec<Span> = self.files.iter().flatten().copied().collect();
+        used.sort();
+        let mut result = Vec::new();
+        let mut pos = 0;
+        for span in used {
+            if !result.is_empty() || span.pos > 0 {
+                if result.is_empty() {
+                    // free space ahead of the first file
+                    result.push(0);
+                }
+                result.push(span.pos - pos);
+            }
+            result.push(span.len);
+            pos = span.end();
+        }
+        if self.len > pos {
+            if result.is_empty() {
+                result.push(0);
+            }
+            result.push(self.len - pos);
+        }
+        result
+    }
+
+    pub fn to_extended(&self) -> String {
+        self.lengths()
+            .iter()
+            .map(|len| len.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    // the dense format, None when some length needs more than one digit
+    pub fn to_dense(&self) -> Option<String> {
+        self.lengths()
+            .iter()
+            .map(|&len| char::from_digit(len as u32, 10).filter(|_| len < 10))
+            .collect()
+    }
+
+    pub fn checksum(&self) -> u128 {
+        self.files
+            .iter()
+            .enumerate()
+            .flat_map(|(id, spans)| spans.iter().map(move |span| (id as u128, span)))
+            .map(|(id, span)| {
+                let (pos, len) = (span.pos as u128, span.len as u128);
+                // pos + (pos + 1) + ... + (pos + len - 1)
+                id * (pos * len + len * len.saturating_sub(1) / 2)
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_dense_map() {
+        let disk = Disk::from_dense("12345\n").unwrap();
+        assert_eq!(disk.len, 15);
+        assert_eq!(
+            disk.files,
+            vec![
+                vec![Span { pos: 0, len: 1 }],
+                vec![Span { pos: 3, len: 3 }],
+                vec![Span { pos: 10, len: 5 }],
+            ]
+        );
+        assert_eq!(
+            disk.free(),
+            vec![Span { pos: 1, len: 2 }, Span { pos: 6, len: 4 }]
+        );
+    }
+
+    #[test]
+    fn should_keep_trailing_free_space() {
+        let disk = Disk::from_dense("1203").unwrap();
+        assert_eq!(disk.len, 6);
+        assert_eq!(
+            disk.free(),
+            vec![Span { pos: 1, len: 2 }, Span { pos: 3, len: 3 }]
+        );
+    }
+
+    #[test]
+    fn should_sum_positions_times_ids() {
+        // 0..111....22222
+        let disk = Disk::from_dense("12345").unwrap();
+        assert_eq!(disk.checksum(), (3 + 4 + 5) + 2 * (10 + 11 + 12 + 13 + 14));
+    }
+
+    #[test]
+    fn should_render_like_puzzle() {
+        assert_eq!(
+            Disk::from_dense("12345").unwrap().render().unwrap(),
+            "0..111....22222"
+        );
+        assert_eq!(
+            Disk::from_dense("2333133121414131402")
+                .unwrap()
+                .render()
+                .unwrap(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+        assert_eq!(
+            Disk::from_dense("101010101010101010101")
+                .unwrap()
+                .render()
+                .unwrap(),
+            "0123456789(10)"
+        );
+        let disk = Disk::from_extended(&format!("1,0,{}", MAX_RENDER)).unwrap();
+        assert_eq!(disk.render(), None);
+    }
+
+    #[test]
+    fn should_round_trip_dense_map() {
+        for input in ["12345", "2333133121414131402", "1203", "0312"] {
+            assert_eq!(
+                Disk::from_dense(input).unwrap().to_dense().as_deref(),
+                Some(input)
+            );
+        }
+    }
+
+    #[test]
+    fn should_encode_moved_files() {
+        let disk = Disk {
+            len: 12,
+            files: vec![
+                vec![Span { pos: 0, len: 1 }, Span { pos: 6, len: 2 }],
+                vec![Span { pos: 1, len: 3 }],
+            ],
+        };
+        assert_eq!(disk.render().unwrap(), "0111..00....");
+        assert_eq!(disk.lengths(), vec![1, 0, 3, 2, 2, 4]);
+        assert_eq!(disk.to_dense().as_deref(), Some("103224"));
+
+        let disk = Disk {
+            len: 12,
+            files: vec![vec![Span { pos: 2, len: 10 }]],
+        };
+        assert_eq!(disk.lengths(), vec![0, 2, 10]);
+        assert_eq!(disk.to_dense(), None);
+    }
+
+    #[test]
+    fn should_reject_stray_characters() {
+        assert_eq!(
+            Disk::from_dense("12a4\n5\n"),
+            Err(ParseError::Stray(vec![(2, 'a'), (4, '\n')]))
+        );
+        assert_eq!(
+            Disk::from_dense(" 12").unwrap_err().to_string(),
+            "unexpected ' ' at offset 0"
+        );
+        assert_eq!(
+            Disk::from_extended("1,2;3,x").unwrap_err().to_string(),
+            "unexpected ';' at offset 3, 'x' at offset 6"
+        );
+    }
+
+    #[test]
+    fn should_parse_extended_map() {
+        let dense = Disk::from_dense("2333133121414131402").unwrap();
+        let extended = Disk::from_extended("2,3,3,3,1,3,3,1,2,1,4,1,4,1,3,1,4,0,2\n").unwrap();
+        assert_eq!(dense, extended);
+
+        let disk = Disk::from_extended("12,0,300").unwrap();
+        assert_eq!(disk.len, 312);
+        assert_eq!(
+            disk.files,
+            vec![
+                vec![Span { pos: 0, len: 12 }],
+                vec![Span { pos: 12, len: 300 }]
+            ]
+        );
+        assert_eq!(disk.to_extended(), "12,0,300");
+        assert_eq!(disk.to_dense(), None);
+        assert_eq!(Disk::from_extended(""), Disk::from_dense(""));
+        // a single length is one file, not three digits
+        let disk = Disk::from_extended("300").unwrap();
+        assert_eq!(disk.files, vec![vec![Span { pos: 0, len: 300 }]]);
+        assert_eq!(Disk::from_dense("300").unwrap().files.len(), 2);
+    }
+
+    #[test]
+    fn should_reject_bad_lengths() {
+        assert_eq!(
+            Disk::from_extended("1,,2"),
+            Err(ParseError::BadLength { offset: 2 })
+        );
+        assert_eq!(
+            Disk::from_extended("1,2,"),
+            Err(ParseError::BadLength { offset: 4 })
+        );
+        assert_eq!(
+            Disk::from_extended("3,99999999999999999999999"),
+            Err(ParseError::BadLength { offset: 2 })
+        );
+        let max = usize::MAX.to_string();
+        assert_eq!(
+            Disk::from_extended(&format!("1,{}", max)),
+            Err(ParseError::BadLength { offset: 2 })
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::disk::Span;
+
+// The free spans of a disk in order of position, for finding where a file
+// goes in O(log n) however many different lengths there are. Spans only ever
+// shrink from the front as files move in, so they stay in order.
+//
+// A segment tree keeps the longest span under every node, which finds the
+// leftmost span of at least some length and the longest one before some
+// position. For the shortest span of at least some length there is also a
+// `ByLength` index, only built when asked for.
+#[derive(Debug)]
+pub struct FreeSpans {
+    spans: Vec<Span>,
+    // leaves, a power of two
+    size: usize,
+    // node 1 is the root, the children of n are 2n and 2n + 1
+    longest: Vec<usize>,
+    by_len: Option<ByLength>,
+}
+
+impl FreeSpans {
+    pub fn new(spans: Vec<Span>, sorted: bool) -> Self {
+        let size = spans.len().next_power_of_two();
+        let mut longest = vec![0; 2 * size];
+        for (i, span) in spans.iter().enumerate() {
+            longest[size + i] = span.len;
+        }
+        for node in (1..size).rev() {
+            longest[node] = longest[2 * node].max(longest[2 * node + 1]);
+        }
+        let by_len = sorted.then(|| {
+            let mut by_len = ByLength::new();
+            for (i, span) in spans.iter().enumerate() {
+                by_len.insert((span.len, i));
+            }
+            by_len
+        });
+        FreeSpans {
+            spans,
+            size,
+            longest,
+            by_len,
+        }
+    }
+
+    // spans starting left of `pos` are the ones before this index
+    fn before(&self, pos: usize) -> usize {
+        self.spans.partition_point(|span| span.pos < pos)
+    }
+
+    // the leftmost span of at least `len` left of `pos`
+    pub fn first_fit(&self, len: usize, pos: usize) -> Option<usize> {
+        let i = self.leftmost(len)?;
+        (i < self.before(pos)).then_some(i)
+    }
+
+    // the shortest span of at least `len` left of `pos`, the leftmost of those
+    pub fn best_fit(&self, len: usize, pos: usize) -> Option<usize> {
+        let by_len = self.by_len.as_ref().expect("built without sorted lengths");
+        by_len.first(len, self.before(pos))
+    }
+
+    // the longest span left of `pos` if `len` fits, the leftmost of those
+    pub fn worst_fit(&self, len: usize, pos: usize) -> Option<usize> {
+        let longest = self
+            .nodes_before(self.before(pos))
+            .map(|node| self.longest[node])
+            .max()?;
+        if longest < len {
+            return None;
+        }
+        self.leftmost(longest)
+    }
+
+    fn leftmost(&self, len: usize) -> Option<usize> {
+        if self.spans.is_empty() || self.longest[1] < len {
+            return None;
+        }
+        let mut node = 1;
+        while node < self.size {
+            node = if self.longest[2 * node] >= len {
+                2 * node
+            } else {
+                2 * node + 1
+            };
+        }
+        Some(node - self.size)
+    }
+
+    // the nodes that together cover the first `end` leaves
+    fn nodes_before(&self, end: usize) -> impl Iterator<Item = usize> {
+        let (mut lo, mut hi) = (self.size, self.size + end);
+        let mut result = Vec::new();
+        while lo < hi {
+            if lo % 2 == 1 {
+                result.push(lo);
+                lo += 1;
+            }
+            if hi % 2 == 1 {
+                hi -= 1;
+                result.push(hi);
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+        result.into_iter()
+    }
+
+    // Hands out the first `len` blocks of span `i`
+    pub fn take(&mut self, i: usize, len: usize) -> Span {
+        let span = self.spans[i];
+        let rest = Span {
+            pos: span.pos + len,
+            len: span.len - len,
+        };
+        self.spans[i] = rest;
+
+        let mut node = self.size + i;
+        self.longest[node] = rest.len;
+        while node > 1 {
+            node /= 2;
+            self.longest[node] = self.longest[2 * node].max(self.longest[2 * node + 1]);
+        }
+        if let Some(by_len) = &mut self.by_len {
+            by_len.remove((span.len, i));
+            by_len.insert((rest.len, i));
+        }
+        Span { pos: span.pos, len }
+    }
+}
+
+const NIL: usize = usize::MAX;
+
+#[derive(Debug)]
+struct Node {
+    key: (usize, usize),
+    priority: u64,
+    left: usize,
+    right: usize,
+    // smallest span index in this subtree
+    min_index: usize,
+}
+
+// (length, span index) pairs in a treap, every subtree knowing its smallest
+// span index. That finds the shortest span of at least some length among the
+// first so many spans in O(log n). Nodes live in one Vec and link by index.
+#[derive(Debug)]
+struct ByLength {
+    nodes: Vec<Node>,
+    root: usize,
+    seed: u64,
+}
+
+impl ByLength {
+    fn new() -> Self {
+        ByLength {
+            nodes: Vec::new(),
+            root: NIL,
+            seed: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    fn min_index(&self, node: usize) -> usize {
+        if node == NIL {
+            usize::MAX
+        } else {
+            self.nodes[node].min_index
+        }
+    }
+
+    fn update(&mut self, node: usize) {
+        let Node {
+            key, left, right, ..
+        } = self.nodes[node];
+        self.nodes[node].min_index = key.1.min(self.min_index(left)).min(self.min_index(right));
+    }
+
+    // keys below `key` and the rest
+    fn split(&mut self, node: usize, key: (usize, usize)) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+        if self.nodes[node].key < key {
+            let (left, right) = self.split(self.nodes[node].right, key);
+            self.nodes[node].right = left;
+            self.update(node);
+            (node, right)
+        } else {
+            let (left, right) = self.split(self.nodes[node].left, key);
+            self.nodes[node].left = right;
+            self.update(node);
+            (left, node)
+        }
+    }
+
+    // every key in `a` below every key in `b`
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let right = self.merge(self.nodes[a].right, b);
+            self.nodes[a].right = right;
+            self.update(a);
+            a
+        } else {
+            let left = self.merge(a, self.nodes[b].left);
+            self.nodes[b].left = left;
+            self.update(b);
+            b
+        }
+    }
+
+    fn insert(&mut self, key: (usize, usize)) {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.nodes.push(Node {
+            key,
+            priority: self.seed,
+            left: NIL,
+            right: NIL,
+            min_index: key.1,
+        });
+        let node = self.nodes.len() - 1;
+        let (left, right) = self.split(self.root, key);
+        let left = self.merge(left, node);
+        self.root = self.merge(left, right);
+    }
+
+    // the node itself stays behind in `nodes`, unlinked
+    fn remove(&mut self, key: (usize, usize)) {
+        let (left, rest) = self.split(self.root, key);
+        let (_, right) = self.split(rest, (key.0, key.1 + 1));
+        self.root = self.merge(left, right);
+    }
+
+    // the span index of the smallest key of at least (len, 0) with an index
+    // below `end`
+    fn first(&self, len: usize, end: usize) -> Option<usize> {
+        self.first_in(self.root, len, end)
+    }
+
+    fn first_in(&self, node: usize, len: usize, end: usize) -> Option<usize> {
+        if node == NIL || self.min_index(node) >= end {
+            return None;
+        }
+        let Node {
+            key, left, right, ..
+        } = self.nodes[node];
+        if key.0 < len {
+            return self.first_in(right, len, end);
+        }
+        if let Some(i) = self.first_in(left, len, end) {
+            return Some(i);
+        }
+        if key.1 < end {
+            return Some(key.1);
+        }
+        // everything on the right is long enough, the leftmost with a small
+        // enough index will do
+        let mut node = right;
+        while node != NIL {
+            let Node {
+                key, left, right, ..
+            } = self.nodes[node];
+            if self.min_index(left) < end {
+                node = left;
+            } else if key.1 < end {
+                return Some(key.1);
+            } else {
+                node = right;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(lens: &[usize]) -> Vec<Span> {
+        let mut pos = 0;
+        lens.iter()
+            .map(|&len| {
+                pos += 1;
+                let span = Span { pos, len };
+                pos += len;
+                span
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_find_each_fit() {
+        // .(3)..(1)..(5)..(2)..(5)
+        let free = FreeSpans::new(spans(&[3, 1, 5, 2, 5]), true);
+        assert_eq!(free.first_fit(2, 100), Some(0));
+        assert_eq!(free.first_fit(4, 100), Some(2));
+        assert_eq!(free.first_fit(4, 7), None);
+        assert_eq!(free.best_fit(2, 100), Some(3));
+        assert_eq!(free.best_fit(4, 100), Some(2));
+        assert_eq!(free.best_fit(2, 9), Some(0));
+        assert_eq!(free.worst_fit(1, 100), Some(2));
+        assert_eq!(free.worst_fit(1, 6), Some(0));
+        assert_eq!(free.worst_fit(6, 100), None);
+    }
+
+    #[test]
+    fn should_shrink_taken_spans() {
+        let mut free = FreeSpans::new(spans(&[3, 1, 5]), true);
+        assert_eq!(free.take(2, 4), Span { pos: 7, len: 4 });
+        assert_eq!(free.first_fit(2, 100), Some(0));
+        assert_eq!(free.worst_fit(1, 100), Some(0));
+        assert_eq!(free.best_fit(1, 100), Some(1));
+        assert_eq!(free.take(0, 3), Span { pos: 1, len: 3 });
+        assert_eq!(free.first_fit(1, 100), Some(1));
+        assert_eq!(free.first_fit(2, 100), None);
+        assert_eq!(free.best_fit(2, 100), None);
+
+        let free = FreeSpans::new(Vec::new(), true);
+        assert_eq!(free.first_fit(1, 100), None);
+        assert_eq!(free.best_fit(1, 100), None);
+        assert_eq!(free.worst_fit(1, 100), None);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod compact;
+pub mod disk;
+pub mod free;
+pub mod metrics;
+pub mod strategy;
@@ -1,147 +1,225 @@
-#[derive(Debug, PartialEq, Eq, Clone)]
-enum BlockType {
-    Free,
-    File,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct Block {
-    kind: BlockType,
-    size: u8,
-}
+use std::{env, error::Error, fs};
 
-fn build_array(input: &str) -> Vec<Block> {
-    input
-        .trim()
-        .chars()
-        .enumerate()
-        .map(|(i, it)| {
-            let kind = if i % 2 == 0 {
-                BlockType::File
-            } else {
-                BlockType::Free
-            };
-            let size = it.to_digit(10).unwrap() as u8;
-            Block { kind, size }
-        })
-        .collect()
-}
-
-// Option -> either '.' or file id
-fn blocks_to_memory(blocks: Vec<Block>) -> Vec<Option<u128>> {
-    let mut index = 0;
-    blocks
-        .iter()
-        .flat_map(|it| {
-            let v = if it.kind == BlockType::File {
-                Some(index)
-            } else {
-                None
-            };
-            if it.kind == BlockType::File {
-                index += 1;
-            }
-            vec![v; it.size.into()]
-        })
-        .collect()
-}
+use _9::{
+    disk::{Disk, MAX_RENDER},
+    strategy::{by_name, Blocks, CompactionStrategy, FirstFit, ALL},
+};
 
-fn sort_blocks(blocks: Vec<Option<u128>>) -> Vec<u128> {
-    let mut result = Vec::new();
-    let mut left = 0;
-    let mut right = blocks.len().saturating_sub(1);
-
-    while left <= right {
-        if let Some(v) = blocks[left] {
-            result.push(v);
-            left += 1;
-        } else {
-            while right > left && blocks[right].is_none() {
-                right = right.saturating_sub(1);
+fn main() -> Result<(), Box<dyn Error>> {
+    // --steps <disk map> prints every move made compacting that map, with
+    // the layout after it, instead of solving the puzzle. --strategy <name>
+    // picks how, first-fit by default. --compare lists how every strategy
+    // leaves the disk. --input <file> reads the disk map from a file instead
+    // of the puzzle. With --extended, maps given to --steps or --input are
+    // comma-separated lengths rather than one digit each. Disks longer than
+    // disk::MAX_RENDER blocks are too long for --steps.
+    let mut input_file = None;
+    let mut steps = None;
+    let mut strategy: &dyn CompactionStrategy = &FirstFit;
+    let mut compare = false;
+    let mut extended = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--steps" => steps = Some(args.next().ok_or("--steps needs a disk map")?),
+            "--compare" => compare = true,
+            "--extended" => extended = true,
+            "--input" => input_file = Some(args.next().ok_or("--input needs a file")?),
+            "--strategy" => {
+                let name = args.next().ok_or("--strategy needs a name")?;
+                strategy = by_name(&name).ok_or(format!("unknown strategy {}", name))?;
             }
-            if let Some(v) = blocks[right] {
-                result.push(v);
-                right = right.saturating_sub(1);
+            _ => return Err(format!("unknown argument {}", arg).into()),
+        }
+    }
+    let parse = if extended {
+        Disk::from_extended
+    } else {
+        Disk::from_dense
+    };
+    if let Some(map) = steps {
+        let disk = parse(&map)?;
+        let layout = disk.render().ok_or(format!(
+            "a disk of {} blocks is too long to show, at most {}",
+            disk.len, MAX_RENDER
+        ))?;
+        println!("{}", layout);
+        let mut log = Vec::new();
+        let disk = strategy.compact(&disk, Some(&mut log));
+        for step in log {
+            println!(
+                "{} {}..{} -> {}..{}",
+                step.id,
+                step.from.pos,
+                step.from.end(),
+                step.to.pos,
+                step.to.end()
+            );
+            if let Some(layout) = step.layout {
+                println!("{}", layout);
             }
-            left += 1;
         }
+        println!("checksum {}", disk.checksum());
+        return Ok(());
     }
 
-    result
-}
+    let disk = match input_file {
+        Some(path) => parse(&fs::read_to_string(path)?)?,
+        None => Disk::from_dense(include_str!("../puzzle.txt"))?,
+    };
+    if compare {
+        for strategy in ALL {
+            println!("{}: {}", strategy.name(), strategy.metrics(&disk));
+        }
+    }
 
-fn checksum(input: &str) -> u128 {
-    let blocks = build_array(input);
-    let memory = blocks_to_memory(blocks);
-    let memory = sort_blocks(memory);
-    memory
-        .iter()
-        .enumerate()
-        .map(|(i, it)| i as u128 * *it as u128)
-        .sum::<u128>()
+    println!("result 2: {}", FirstFit.metrics(&disk).checksum);
+    println!("result 1: {}", Blocks.metrics(&disk).checksum);
+
+    Ok(())
 }
 
-// This removes all abtractions and is a close representation of memory
-fn parse_part2(input: &str) -> Vec<(usize, isize)> {
-    let mut fileid = 0;
-    input
-        .trim()
-        .bytes()
-        .enumerate()
-        .map(|(i, b)| {
-            (
-                (b - b'0') as usize,
-                if i % 2 == 0 {
-                    fileid += 1;
-                    fileid - 1
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use _9::{compact::whole_files, metrics::Metrics};
+
+    // The original part 1, one block at a time.
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    enum BlockType {
+        Free,
+        File,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    struct Block {
+        kind: BlockType,
+        size: u8,
+    }
+
+    fn build_array(input: &str) -> Vec<Block> {
+        input
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(i, it)| {
+                let kind = if i % 2 == 0 {
+                    BlockType::File
                 } else {
-                    -1
-                },
-            )
-        })
-        .collect()
-}
+                    BlockType::Free
+                };
+                let size = it.to_digit(10).unwrap() as u8;
+                Block { kind, size }
+            })
+            .collect()
+    }
 
-fn efficient_checksum(mut files: Vec<(usize, isize)>) -> usize {
-    let mut i = files.len() - 1;
-    while i > 0 {
-        let (size, id) = files[i];
-        if id == -1 {
-            i -= 1;
-            continue;
-        }
-        if let Some(j) = files[0..i]
+    // Option -> either '.' or file id
+    fn blocks_to_memory(blocks: Vec<Block>) -> Vec<Option<u128>> {
+        let mut index = 0;
+        blocks
             .iter()
-            .position(|&(s, id)| id == -1 && size <= s)
-        {
-            let s = files[j].0;
-            files[j] = (size, id);
-            files[i] = (size, -1);
-            if size < s {
-                files.insert(j + 1, (s - size, -1));
+            .flat_map(|it| {
+                let v = if it.kind == BlockType::File {
+                    Some(index)
+                } else {
+                    None
+                };
+                if it.kind == BlockType::File {
+                    index += 1;
+                }
+                vec![v; it.size.into()]
+            })
+            .collect()
+    }
+
+    fn sort_blocks(blocks: Vec<Option<u128>>) -> Vec<u128> {
+        let mut result = Vec::new();
+        let mut left = 0;
+        let mut right = blocks.len().saturating_sub(1);
+
+        while left <= right {
+            if let Some(v) = blocks[left] {
+                result.push(v);
+                left += 1;
+            } else {
+                while right > left && blocks[right].is_none() {
+                    right = right.saturating_sub(1);
+                }
+                if let Some(v) = blocks[right] {
+                    result.push(v);
+                    right = right.saturating_sub(1);
+                }
+                left += 1;
             }
         }
-        i -= 1;
-    }
-    files
-        .iter()
-        .flat_map(|&(s, id)| (0..s).map(move |_| id))
-        .enumerate()
-        .map(|(i, id)| if id == -1 { 0 } else { i * id as usize })
-        .sum()
-}
 
-fn main() {
-    let input = include_str!("../puzzle.txt");
-    let checksum = checksum(input);
-    let disk = parse_part2(input);
-    println!("result 2: {}", efficient_checksum(disk));
-    println!("result 1: {}", checksum);
-}
+        result
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn checksum(input: &str) -> u128 {
+        let blocks = build_array(input);
+        let memory = blocks_to_memory(blocks);
+        let memory = sort_blocks(memory);
+        memory
+            .iter()
+            .enumerate()
+            .map(|(i, it)| i as u128 * *it)
+            .sum::<u128>()
+    }
+
+    // The original part 2, scanning for a free span for every file. Kept to
+    // check `whole_files` against.
+
+    // This removes all abtractions and is a close representation of memory
+    fn parse_part2(input: &str) -> Vec<(usize, isize)> {
+        let mut fileid = 0;
+        input
+            .trim()
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| {
+                (
+                    (b - b'0') as usize,
+                    if i % 2 == 0 {
+                        fileid += 1;
+                        fileid - 1
+                    } else {
+                        -1
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn efficient_checksum(mut files: Vec<(usize, isize)>) -> usize {
+        let mut i = files.len() - 1;
+        while i > 0 {
+            let (size, id) = files[i];
+            if id == -1 {
+                i -= 1;
+                continue;
+            }
+            if let Some(j) = files[0..i]
+                .iter()
+                .position(|&(s, id)| id == -1 && size <= s)
+            {
+                let s = files[j].0;
+                files[j] = (size, id);
+                files[i] = (size, -1);
+                if size < s {
+                    files.insert(j + 1, (s - size, -1));
+                }
+            }
+            i -= 1;
+        }
+        files
+            .iter()
+            .flat_map(|&(s, id)| (0..s).map(move |_| id))
+            .enumerate()
+            .map(|(i, id)| if id == -1 { 0 } else { i * id as usize })
+            .sum()
+    }
 
     #[test]
     fn should_move_file_in_array() {
@@ -159,4 +237,47 @@ mod tests {
         let actual = efficient_checksum(given);
         assert_eq!(expected, actual);
     }
+
+    // a fixed pseudo-random sequence of `len` digits
+    fn random_digits(len: usize, mut seed: u64) -> String {
+        (0..len)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                char::from(b'0' + (seed % 10) as u8)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_agree_with_linear_scan() {
+        for (len, seed) in [(2001, 0x2545_f491), (8001, 7), (8000, 12345)] {
+            let digits = random_digits(len, seed);
+            let expected = efficient_checksum(parse_part2(&digits)) as u128;
+            let actual = whole_files(&Disk::from_dense(&digits).unwrap()).checksum();
+            assert_eq!(expected, actual, "{} digits from {}", len, seed);
+        }
+    }
+
+    // cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn should_compact_millions_of_entries_quickly() {
+        let disk = Disk::from_dense(&random_digits(2_000_001, 42)).unwrap();
+        let start = std::time::Instant::now();
+        let compacted = whole_files(&disk);
+        let elapsed = start.elapsed();
+        assert!(elapsed.as_millis() < 500, "took {:?}", elapsed);
+        assert_eq!(Metrics::new(&compacted).split_files, 0);
+    }
+
+    #[test]
+    fn should_agree_with_block_moves() {
+        for digits in ["2333133121414131402", "12345", "1111122", "90909"] {
+            let expected = checksum(digits);
+            let actual = Blocks.metrics(&Disk::from_dense(digits).unwrap()).checksum;
+            assert_eq!(expected, actual, "{}", digits);
+        }
+    }
 }
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::disk::Disk;
+
+// How tidy a disk is after compacting, to compare strategies by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    pub checksum: u128,
+    // pieces the files are in, one per file when none is split
+    pub fragments: usize,
+    pub split_files: usize,
+    // free spans ahead of the last used block and the longest of them
+    pub holes: usize,
+    pub largest_hole: usize,
+    // one past the last used block
+    pub used_end: usize,
+}
+
+impl Metrics {
+    pub fn new(disk: &Disk) -> Self {
+        let used_end = disk
+            .files
+            .iter()
+            .flatten()
+            .filter(|span| span.len > 0)
+            .map(|span| span.end())
+            .max()
+            .unwrap_or(0);
+        let holes: Vec<usize> = disk
+            .free()
+            .iter()
+            .filter(|span| span.end() <= used_end)
+            .map(|span| span.len)
+            .collect();
+        Metrics {
+            checksum: disk.checksum(),
+            fragments: disk.files.iter().map(|spans| spans.len()).sum(),
+            split_files: disk.files.iter().filter(|spans| spans.len() > 1).count(),
+            holes: holes.len(),
+            largest_hole: holes.iter().copied().max().unwrap_or(0),
+            used_end,
+        }
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checksum {}, {} fragments, {} split files, {} holes (largest {}), used up to {}",
+            self.checksum,
+            self.fragments,
+            self.split_files,
+            self.holes,
+            self.largest_hole,
+            self.used_end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_measure_parsed_disk() {
+        // 00...111...2...333.44.5555.6666.777.888899
+        let metrics = Metrics::new(&Disk::from_dense("2333133121414131402").unwrap());
+        assert_eq!(
+            metrics,
+            Metrics {
+                checksum: Disk::from_dense("2333133121414131402").unwrap().checksum(),
+                fragments: 10,
+                split_files: 0,
+                holes: 8,
+                largest_hole: 3,
+                used_end: 42,
+            }
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+
+use crate::{
+    compact::{blocks, defragment, fit_files, repeated, Fit, Step},
+    disk::Disk,
+    metrics::Metrics,
+};
+
+pub trait CompactionStrategy: fmt::Debug {
+    fn name(&self) -> &str;
+
+    // The disk after compacting, every move goes to `log` if there is one
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk;
+
+    fn metrics(&self, disk: &Disk) -> Metrics {
+        Metrics::new(&self.compact(disk, None))
+    }
+}
+
+// part 1, block by block
+#[derive(Debug)]
+pub struct Blocks;
+
+impl CompactionStrategy for Blocks {
+    fn name(&self) -> &str {
+        "blocks"
+    }
+
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk {
+        blocks(disk, log)
+    }
+}
+
+// part 2, whole files to the leftmost span they fit in
+#[derive(Debug)]
+pub struct FirstFit;
+
+impl CompactionStrategy for FirstFit {
+    fn name(&self) -> &str {
+        "first-fit"
+    }
+
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk {
+        fit_files(disk, Fit::First, log)
+    }
+}
+
+#[derive(Debug)]
+pub struct BestFit;
+
+impl CompactionStrategy for BestFit {
+    fn name(&self) -> &str {
+        "best-fit"
+    }
+
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk {
+        fit_files(disk, Fit::Best, log)
+    }
+}
+
+#[derive(Debug)]
+pub struct WorstFit;
+
+impl CompactionStrategy for WorstFit {
+    fn name(&self) -> &str {
+        "worst-fit"
+    }
+
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk {
+        fit_files(disk, Fit::Worst, log)
+    }
+}
+
+// first fit until nothing moves anymore
+#[derive(Debug)]
+pub struct Repeated;
+
+impl CompactionStrategy for Repeated {
+    fn name(&self) -> &str {
+        "repeated"
+    }
+
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk {
+        repeated(disk, log)
+    }
+}
+
+// whole files, no gaps
+#[derive(Debug)]
+pub struct Defragment;
+
+impl CompactionStrategy for Defragment {
+    fn name(&self) -> &str {
+        "defragment"
+    }
+
+    fn compact(&self, disk: &Disk, log: Option<&mut Vec<Step>>) -> Disk {
+        defragment(disk, log)
+    }
+}
+
+pub const ALL: [&dyn CompactionStrategy; 6] = [
+    &Blocks,
+    &FirstFit,
+    &BestFit,
+    &WorstFit,
+    &Repeated,
+    &Defragment,
+];
+
+pub fn by_name(name: &str) -> Option<&'static dyn CompactionStrategy> {
+    ALL.into_iter().find(|strategy| strategy.name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    #[test]
+    fn should_find_by_name() {
+        for strategy in ALL {
+            assert_eq!(by_name(strategy.name()).unwrap().name(), strategy.name());
+        }
+        assert!(by_name("random").is_none());
+    }
+
+    #[test]
+    fn should_solve_both_parts() {
+        let disk = Disk::from_dense(EXAMPLE).unwrap();
+        assert_eq!(Blocks.metrics(&disk).checksum, 1928);
+        assert_eq!(FirstFit.metrics(&disk).checksum, 2858);
+    }
+
+    #[test]
+    fn should_keep_blocks() {
+        let disk = Disk::from_dense(EXAMPLE).unwrap();
+        for strategy in ALL {
+            let actual = strategy.compact(&disk, None);
+            let mut blocks: Vec<char> = actual.render().unwrap().chars().collect();
+            let mut expected: Vec<char> = disk.render().unwrap().chars().collect();
+            blocks.sort();
+            expected.sort();
+            assert_eq!(blocks, expected, "{}", strategy.name());
+        }
+    }
+
+    #[test]
+    fn should_pick_span_by_fit() {
+        let disk = Disk::from_dense("15122").unwrap();
+        assert_eq!(disk.render().unwrap(), "0.....1..22");
+        assert_eq!(
+            FirstFit.compact(&disk, None).render().unwrap(),
+            "0221......."
+        );
+        assert_eq!(
+            BestFit.compact(&disk, None).render().unwrap(),
+            "01.....22.."
+        );
+        assert_eq!(
+            WorstFit.compact(&disk, None).render().unwrap(),
+            "0221......."
+        );
+
+        let disk = Disk::from_dense("13152").unwrap();
+        assert_eq!(disk.render().unwrap(), "0...1.....22");
+        assert_eq!(
+            FirstFit.compact(&disk, None).render().unwrap(),
+            "0221........"
+        );
+        assert_eq!(
+            BestFit.compact(&disk, None).render().unwrap(),
+            "0221........"
+        );
+        assert_eq!(
+            WorstFit.compact(&disk, None).render().unwrap(),
+            "01...22....."
+        );
+    }
+
+    #[test]
+    fn should_move_again() {
+        // 3 can move again once 2 has made room next to its old spot
+        let disk = Disk::from_dense("1111122").unwrap();
+        assert_eq!(disk.render().unwrap(), "0.1.2..33");
+        assert_eq!(FirstFit.compact(&disk, None).render().unwrap(), "021..33..");
+        assert_eq!(Repeated.compact(&disk, None).render().unwrap(), "02133....");
+    }
+
+    #[test]
+    fn should_defragment() {
+        let disk = Disk::from_dense(EXAMPLE).unwrap();
+        let actual = Defragment.compact(&disk, None);
+        assert_eq!(
+            actual.render().unwrap(),
+            "0011123334455556666777888899.............."
+        );
+        let metrics = Metrics::new(&actual);
+        assert_eq!((metrics.holes, metrics.split_files), (0, 0));
+
+        // pieces left behind by moving blocks are put back together
+        let mut log = Vec::new();
+        let actual = Defragment.compact(&Blocks.compact(&disk, None), Some(&mut log));
+        assert_eq!(
+            actual.render().unwrap(),
+            "0099888811127773336666445555.............."
+        );
+        assert_eq!(Metrics::new(&actual).split_files, 0);
+        assert_eq!(log.last().unwrap().layout, actual.render());
+    }
+
+    #[test]
+    fn should_log_blocks_like_puzzle() {
+        let mut log = Vec::new();
+        Blocks.compact(&Disk::from_dense("12345").unwrap(), Some(&mut log));
+        let layouts: Vec<&str> = log
+            .iter()
+            .map(|step| step.layout.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            layouts,
+            vec![
+                "02.111....2222.",
+                "022111....222..",
+                "0221112...22...",
+                "02211122..2....",
+                "022111222......",
+            ]
+        );
+    }
+}